@@ -0,0 +1,95 @@
+use anyhow::Result;
+use arrow_array::builder::{BooleanBuilder, Float64Builder, StringBuilder, TimestampNanosecondBuilder, UInt32Builder, UInt64Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::fs::File;
+use std::sync::Arc;
+
+use crate::market::HourBar;
+
+/// Writes the price bars of every `(ticker, bars)` group into one Parquet file. `tz` is
+/// recorded on the timestamp column so readers can show session-local times.
+pub fn write_bars(file: File, tz: &str, groups: &[(&str, &[HourBar])]) -> Result<()> {
+    let tz: Arc<str> = Arc::from(tz);
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("ticker", DataType::Utf8, false),
+        Field::new("ts", DataType::Timestamp(TimeUnit::Nanosecond, Some(tz.clone())), false),
+        Field::new("o", DataType::Float64, false),
+        Field::new("h", DataType::Float64, false),
+        Field::new("l", DataType::Float64, false),
+        Field::new("c", DataType::Float64, false),
+        Field::new("v", DataType::UInt64, false),
+        Field::new("vwap", DataType::Float64, false),
+        Field::new("notional", DataType::Float64, false),
+        Field::new("n", DataType::UInt32, false),
+        Field::new("adj_c", DataType::Float64, true),
+        Field::new("synthetic", DataType::Boolean, false),
+        Field::new("is_partial", DataType::Boolean, false),
+        Field::new("ret_simple", DataType::Float64, true),
+        Field::new("ret_log", DataType::Float64, true),
+        Field::new("sma", DataType::Float64, true),
+        Field::new("ema", DataType::Float64, true),
+    ]));
+
+    let mut ticker = StringBuilder::new();
+    let mut ts = TimestampNanosecondBuilder::new().with_timezone(tz);
+    let (mut o, mut h, mut l, mut c, mut vwap) = (Float64Builder::new(), Float64Builder::new(), Float64Builder::new(), Float64Builder::new(), Float64Builder::new());
+    let mut notional = Float64Builder::new();
+    let mut v = UInt64Builder::new();
+    let mut n = UInt32Builder::new();
+    let (mut adj_c, mut ret_simple, mut ret_log) = (Float64Builder::new(), Float64Builder::new(), Float64Builder::new());
+    let (mut sma, mut ema) = (Float64Builder::new(), Float64Builder::new());
+    let (mut synthetic, mut is_partial) = (BooleanBuilder::new(), BooleanBuilder::new());
+
+    for (symbol, bars) in groups {
+        for b in bars.iter() {
+            ticker.append_value(symbol);
+            ts.append_option(b.ts_local.timestamp_nanos_opt());
+            o.append_value(b.o);
+            h.append_value(b.h);
+            l.append_value(b.l);
+            c.append_value(b.c);
+            v.append_value(b.v);
+            vwap.append_value(b.vwap);
+            notional.append_value(b.notional);
+            n.append_value(b.sample_count);
+            adj_c.append_option(b.adj_c);
+            synthetic.append_value(b.synthetic);
+            is_partial.append_value(b.is_partial);
+            ret_simple.append_option(b.ret_simple);
+            ret_log.append_option(b.ret_log);
+            sma.append_option(b.sma);
+            ema.append_option(b.ema);
+        }
+    }
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(ticker.finish()),
+        Arc::new(ts.finish()),
+        Arc::new(o.finish()),
+        Arc::new(h.finish()),
+        Arc::new(l.finish()),
+        Arc::new(c.finish()),
+        Arc::new(v.finish()),
+        Arc::new(vwap.finish()),
+        Arc::new(notional.finish()),
+        Arc::new(n.finish()),
+        Arc::new(adj_c.finish()),
+        Arc::new(synthetic.finish()),
+        Arc::new(is_partial.finish()),
+        Arc::new(ret_simple.finish()),
+        Arc::new(ret_log.finish()),
+        Arc::new(sma.finish()),
+        Arc::new(ema.finish()),
+    ];
+    let batch = RecordBatch::try_new(schema.clone(), columns)?;
+
+    let props = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+    let mut writer = ArrowWriter::try_new(file, schema, Some(props))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
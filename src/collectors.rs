@@ -1,172 +1,835 @@
-use anyhow::{Context, Result};
-use std::time::Duration;
+use crate::error::{Result, ScrapyError};
+use crate::net::{self, ClientOpts};
+use crate::robots;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 use quick_xml::events::Event;
 use quick_xml::reader::Reader;
 use quick_xml::escape::unescape;
-use serde::Deserialize;
-use scraper::{Html, Selector}; 
-use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, ACCEPT_LANGUAGE};
+use serde::{Deserialize, Serialize};
+use scraper::{ElementRef, Html, Selector};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct NewsItem {
     pub datetime: String,
     pub headline: String,
     pub source: String,
+    pub url: String, // publisher URL when the aggregator link could be resolved (and followed), else the feed link
     pub content_snippet: String, 
+    pub sentiment: f32, // lexicon score in [-1, 1] over headline + snippet, see `score_sentiment`
 }
 
 pub trait NewsCollector {
     fn collect_news(&self, ticker: &str, window_days: i64) -> Result<Vec<NewsItem>>;
 }
 
-pub struct GoogleNewsCollector;
-impl NewsCollector for GoogleNewsCollector {
-    fn collect_news(&self, ticker: &str, _window_days: i64) -> Result<Vec<NewsItem>> {
-        let url = format!("https://news.google.com/rss/search?q={}+stock&hl=en-US&gl=US&ceid=US:en", ticker);
+/// Upper bound on articles scraped per ticker, whatever the caller asks for.
+pub const MAX_NEWS_ITEMS: usize = 25;
+/// Scraped paragraphs shorter than this many bytes are treated as boilerplate.
+pub const DEFAULT_MIN_PARAGRAPH_LEN: usize = 50;
+/// A scraped body shorter than this many bytes counts as a failed scrape.
+pub const DEFAULT_MIN_SNIPPET_LEN: usize = 50;
+/// Per-request timeout for feeds.
+pub const DEFAULT_NEWS_TIMEOUT: Duration = Duration::from_secs(8);
+/// Per-request timeout for article pages.
+pub const DEFAULT_ARTICLE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Scraped paragraphs containing any of these (lowercase) are dropped as boilerplate.
+pub const DEFAULT_BOILERPLATE_KEYWORDS: &[&str] = &["cookie", "subscribe", "rights reserved", "click here", "javascript", "adblock", "promo"];
 
-        let client = reqwest::blocking::Client::builder()
-            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/121.0.0.0 Safari/537.36")
-            .timeout(Duration::from_secs(8)) 
-            .build()?;
-        
-        let resp = client.get(&url).send()?;
-        if !resp.status().is_success() {
-             return Ok(vec![]);
+/// Language and region of the Google News and Yahoo Finance feeds: Google's `hl`, `gl` and
+/// `ceid` parameters (Yahoo gets `lang` and `region`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewsLocale {
+    pub hl: String, // e.g. "en-GB"
+    pub gl: String, // e.g. "GB"
+    pub ceid: String, // e.g. "GB:en"
+}
+
+impl Default for NewsLocale {
+    /// US English, the feeds' original parameters.
+    fn default() -> Self {
+        NewsLocale::new("en-US", "US")
+    }
+}
+
+impl NewsLocale {
+    /// Locale for language `hl` and country `gl`; `ceid` is derived as `{gl}:{language}`.
+    pub fn new(hl: &str, gl: &str) -> Self {
+        let language = hl.split('-').next().unwrap_or(hl);
+        NewsLocale { hl: hl.to_string(), gl: gl.to_string(), ceid: format!("{}:{}", gl, language) }
+    }
+}
+
+/// What a news item carries when its article couldn't be scraped (or came back too short).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FallbackPolicy {
+    /// The feed's own description as `(Summary): ...`, or "Content unavailable." without one
+    #[default]
+    RssDescription,
+    /// Drop the item
+    Skip,
+    /// Always "Content unavailable."
+    MarkUnavailable,
+}
+
+/// Which RSS feed(s) `RssNewsCollector` reads headlines from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NewsSource {
+    Google,
+    Yahoo,
+    Bing,
+    /// Every feed above, deduplicated and interleaved newest first.
+    Merge,
+}
+
+impl NewsSource {
+    /// (label used when an item names no publisher, feed URL) for each feed this source reads.
+    fn feeds(self, ticker: &str, locale: &NewsLocale) -> Vec<(&'static str, String)> {
+        let google = ("Google News", format!("https://news.google.com/rss/search?q={}+stock&hl={}&gl={}&ceid={}", ticker, locale.hl, locale.gl, locale.ceid));
+        let yahoo = ("Yahoo Finance", format!("https://feeds.finance.yahoo.com/rss/2.0/headline?s={}&region={}&lang={}", ticker, locale.gl, locale.hl));
+        let bing = ("Bing News", format!("https://www.bing.com/news/search?q={}+stock&format=rss", ticker));
+        match self {
+            NewsSource::Google => vec![google],
+            NewsSource::Yahoo => vec![yahoo],
+            NewsSource::Bing => vec![bing],
+            NewsSource::Merge => vec![google, yahoo, bing],
         }
-        let xml_content = resp.text()?;
-        
-        let mut reader = Reader::from_str(&xml_content);
-        reader.trim_text(true);
+    }
+}
+
+/// One `<item>` of an RSS feed, with the description already stripped to plain text.
+#[derive(Debug, Clone, Default)]
+pub struct RawItem {
+    pub date: String, // UTC RFC3339 when the pubDate parsed, else as found in the feed
+    pub title: String,
+    pub source: String,
+    pub link: String,
+    pub description: String,
+}
+
+pub struct RssNewsCollector {
+    pub source: NewsSource,
+    pub max_items: usize, // RSS items to scrape, capped at MAX_NEWS_ITEMS
+    pub concurrency: usize, // article pages fetched in parallel
+    pub respect_robots: bool, // skip article pages robots.txt disallows, keeping the RSS snippet
+    pub min_paragraph_len: usize, // shorter scraped paragraphs are dropped as boilerplate
+    pub min_snippet_len: usize, // shorter scraped bodies trigger `fallback`
+    pub fallback: FallbackPolicy,
+    pub snippet_max_chars: Option<usize>, // cut snippets at a sentence boundary near this many chars
+    pub snippet_max_sentences: Option<usize>, // keep at most this many sentences per snippet
+    pub feed_cache: Option<PathBuf>, // directory for feed bodies and their ETag/Last-Modified
+    pub locale: NewsLocale,
+    pub timeout: Duration, // per feed request
+    pub article_timeout: Duration, // per article page request
+    pub boilerplate_keywords: Vec<String>, // lowercase; matching paragraphs are dropped
+    pub headlines_only: bool, // skip article pages; snippets are the feed's description, if any
+    pub deadline: Option<Instant>, // no feed or article requests are started after this
+    pub content_selectors: Vec<Selector>, // tried in order before the readability scoring; see `parse_selector`
+}
+
+impl Default for RssNewsCollector {
+    fn default() -> Self {
+        RssNewsCollector {
+            source: NewsSource::Google,
+            max_items: 10,
+            concurrency: 4,
+            respect_robots: true,
+            min_paragraph_len: DEFAULT_MIN_PARAGRAPH_LEN,
+            min_snippet_len: DEFAULT_MIN_SNIPPET_LEN,
+            fallback: FallbackPolicy::RssDescription,
+            snippet_max_chars: None,
+            snippet_max_sentences: None,
+            feed_cache: None,
+            locale: NewsLocale::default(),
+            timeout: DEFAULT_NEWS_TIMEOUT,
+            article_timeout: DEFAULT_ARTICLE_TIMEOUT,
+            boilerplate_keywords: DEFAULT_BOILERPLATE_KEYWORDS.iter().map(|k| k.to_string()).collect(),
+            headlines_only: false,
+            deadline: None,
+            content_selectors: Vec::new(),
+        }
+    }
+}
+
+impl RssNewsCollector {
+    /// Starts from the defaults, e.g.
+    /// `RssNewsCollector::builder().max_items(10).hl("en-GB").gl("GB").build()`.
+    pub fn builder() -> RssNewsCollectorBuilder {
+        RssNewsCollectorBuilder::default()
+    }
+}
 
+/// Fluent construction of an `RssNewsCollector`; unset options keep their defaults.
+#[derive(Default)]
+pub struct RssNewsCollectorBuilder {
+    collector: RssNewsCollector,
+    hl: Option<String>,
+    gl: Option<String>,
+}
+
+impl RssNewsCollectorBuilder {
+    pub fn source(mut self, source: NewsSource) -> Self {
+        self.collector.source = source;
+        self
+    }
+
+    /// RSS items to scrape, capped at `MAX_NEWS_ITEMS`.
+    pub fn max_items(mut self, max_items: usize) -> Self {
+        self.collector.max_items = max_items;
+        self
+    }
+
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.collector.concurrency = concurrency;
+        self
+    }
+
+    /// Per feed request.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.collector.timeout = timeout;
+        self
+    }
+
+    /// Per article page request.
+    pub fn article_timeout(mut self, timeout: Duration) -> Self {
+        self.collector.article_timeout = timeout;
+        self
+    }
+
+    /// Paragraphs containing any of these (case-insensitive) are dropped as boilerplate.
+    pub fn boilerplate_keywords(mut self, keywords: Vec<String>) -> Self {
+        self.collector.boilerplate_keywords = keywords.into_iter().map(|k| k.to_lowercase()).collect();
+        self
+    }
+
+    /// Feed language, e.g. "en-GB"; see `NewsLocale::new`.
+    pub fn hl(mut self, hl: &str) -> Self {
+        self.hl = Some(hl.to_string());
+        self
+    }
+
+    /// Feed country, e.g. "GB"; see `NewsLocale::new`.
+    pub fn gl(mut self, gl: &str) -> Self {
+        self.gl = Some(gl.to_string());
+        self
+    }
+
+    /// The collector, with `ceid` derived from `hl` and `gl` when either was set.
+    pub fn build(self) -> RssNewsCollector {
+        let mut collector = self.collector;
+        if self.hl.is_some() || self.gl.is_some() {
+            let hl = self.hl.unwrap_or_else(|| collector.locale.hl.clone());
+            let gl = self.gl.unwrap_or_else(|| collector.locale.gl.clone());
+            collector.locale = NewsLocale::new(&hl, &gl);
+        }
+        collector
+    }
+}
+
+impl NewsCollector for RssNewsCollector {
+    fn collect_news(&self, ticker: &str, window_days: i64) -> Result<Vec<NewsItem>> {
+        let client = net::build_client(ClientOpts { timeout: Some(self.timeout), ..ClientOpts::default() })?;
+
+        // In merge mode one broken feed shouldn't sink the others; fail only if all of them do
         let mut raw_items = Vec::new();
-        let mut buf = Vec::new();
-        let mut in_item = false;
-        
-        let mut current_title = String::new();
-        let mut current_link = String::new();
-        let mut current_date = String::new();
-        let mut current_source = String::new();
-        let mut current_desc = String::new();
-
-        loop {
-            match reader.read_event_into(&mut buf) {
-                Ok(Event::Start(ref e)) => {
-                    match e.name().as_ref() {
-                        b"item" => in_item = true,
-                        b"title" if in_item => current_title = reader.read_text(e.name())?.to_string(),
-                        b"link" if in_item => current_link = reader.read_text(e.name())?.to_string(),
-                        b"pubDate" if in_item => current_date = reader.read_text(e.name())?.to_string(),
-                        b"source" if in_item => current_source = reader.read_text(e.name())?.to_string(),
-                        b"description" if in_item => current_desc = reader.read_text(e.name())?.to_string(),
-                        _ => (),
-                    }
+        let mut first_err = None;
+        let feeds = self.source.feeds(ticker, &self.locale);
+        let feed_count = feeds.len();
+        for (label, url) in feeds {
+            if self.past_deadline() {
+                log::warn!("deadline reached, skipping the {} news feed", label);
+                break;
+            }
+            match fetch_feed(&client, &url, self.feed_cache.as_deref()) {
+                // Items keep their feed's label for attribution when they lack a <source>
+                Ok(items) => raw_items.extend(items.into_iter().map(|item| (label, item))),
+                Err(e) if feed_count > 1 => {
+                    log::warn!("{} news feed failed: {}", label, e);
+                    first_err.get_or_insert(e);
                 }
-                Ok(Event::End(ref e)) => {
-                    if e.name().as_ref() == b"item" {
-                        if !current_link.is_empty() {
-                            // CLEANUP DESCRIPTION
-                            // 1. Unescape HTML entities (e.g. &lt; -> <)
-                            let unescaped = unescape(&current_desc).unwrap_or(std::borrow::Cow::Borrowed(&current_desc));
-                            // 2. Parse as HTML fragment to strip tags
-                            let frag = Html::parse_fragment(&unescaped);
-                            let clean_desc = frag.root_element().text().collect::<Vec<_>>().join(" ");
-                            let clean_desc = clean_desc.trim().to_string();
-
-                            raw_items.push((current_date.clone(), current_title.clone(), current_source.clone(), current_link.clone(), clean_desc));
-                        }
-                        in_item = false;
-                        current_title.clear();
-                        current_link.clear();
-                        current_date.clear();
-                        current_source.clear();
-                        current_desc.clear();
+                Err(e) => return Err(e),
+            }
+        }
+        if raw_items.is_empty() {
+            if let Some(e) = first_err {
+                return Err(e);
+            }
+        }
+
+        // Drop items older than the window; undated items are kept but pushed to the end
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(window_days);
+        let published = |date: &str| chrono::DateTime::parse_from_rfc3339(date).ok();
+        let fetched = raw_items.len();
+        raw_items.retain(|(_, item)| published(&item.date).is_none_or(|dt| dt >= cutoff));
+        log::debug!("{} of {} news item(s) older than {} day(s) dropped", fetched - raw_items.len(), fetched, window_days);
+        if self.source == NewsSource::Merge {
+            // Dedup before scraping so the same story isn't fetched once per feed
+            let before = raw_items.len();
+            raw_items = dedup_by_headline(raw_items, |(_, item)| &item.title, HEADLINE_DEDUP_THRESHOLD);
+            log::debug!("{} duplicate headline(s) across feeds dropped", before - raw_items.len());
+            raw_items.sort_by_key(|(_, item)| std::cmp::Reverse(published(&item.date)));
+        } else {
+            raw_items.sort_by_key(|(_, item)| published(&item.date).is_none());
+        }
+
+        let article_client = net::build_client(ClientOpts {
+            timeout: Some(self.article_timeout),
+            cookie_store: true,
+            accept: net::BROWSER_ACCEPT,
+            ..ClientOpts::default()
+        })?;
+
+        let jobs: Vec<_> = raw_items.into_iter().take(self.max_items.min(MAX_NEWS_ITEMS)).collect();
+
+        // Scrape bodies on a small worker pool; each result lands in its RSS slot so order is preserved
+        let slots: Mutex<Vec<Option<NewsItem>>> = Mutex::new(vec![None; jobs.len()]);
+        let next = AtomicUsize::new(0);
+        thread::scope(|scope| {
+            for _ in 0..self.concurrency.clamp(1, jobs.len().max(1)) {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some((feed, job)) = jobs.get(i) else { break };
+                    let item = self.build_news_item(&article_client, job, feed);
+                    slots.lock().unwrap()[i] = item;
+                });
+            }
+        });
+
+        let items: Vec<NewsItem> = slots.into_inner().unwrap().into_iter().flatten().collect();
+        let scraped = items.len();
+        let items = dedup_news(items, HEADLINE_DEDUP_THRESHOLD);
+        log::debug!("{} duplicate news item(s) dropped after scraping", scraped - items.len());
+        Ok(items)
+    }
+}
+
+/// A feed body kept with its `ETag` / `Last-Modified` validators for conditional GETs.
+struct CachedFeed {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    body: String,
+}
+
+impl CachedFeed {
+    fn path(dir: &Path, url: &str) -> PathBuf {
+        dir.join(format!("{}.xml", crate::fetcher::cache_file_stem(url)))
+    }
+
+    /// Stored as `etag:` / `last-modified:` lines, a blank line, then the body.
+    fn load(path: &Path) -> Option<CachedFeed> {
+        let content = std::fs::read_to_string(path).ok()?;
+        let (head, body) = content.split_once("\n\n")?;
+        let mut feed = CachedFeed { etag: None, last_modified: None, body: body.to_string() };
+        for line in head.lines() {
+            match line.split_once(": ") {
+                Some(("etag", v)) => feed.etag = Some(v.to_string()),
+                Some(("last-modified", v)) => feed.last_modified = Some(v.to_string()),
+                _ => {}
+            }
+        }
+        Some(feed)
+    }
+
+    /// Best effort, like the chart cache: a failed write only costs a full download next time.
+    fn store(&self, path: &Path) {
+        let mut content = String::new();
+        if let Some(etag) = &self.etag {
+            content.push_str(&format!("etag: {}\n", etag));
+        }
+        if let Some(last_modified) = &self.last_modified {
+            content.push_str(&format!("last-modified: {}\n", last_modified));
+        }
+        content.push('\n');
+        content.push_str(&self.body);
+        if path.parent().is_none_or(|dir| std::fs::create_dir_all(dir).is_ok()) {
+            let _ = std::fs::write(path, content);
+        }
+    }
+}
+
+/// Downloads and parses one feed. A non-2xx answer counts as an empty feed rather than an
+/// error. With `cache_dir`, validators from the last download are sent as `If-None-Match` /
+/// `If-Modified-Since` and a 304 reuses the stored body.
+fn fetch_feed(client: &reqwest::blocking::Client, url: &str, cache_dir: Option<&Path>) -> Result<Vec<RawItem>> {
+    let cache_path = cache_dir.map(|dir| CachedFeed::path(dir, url));
+    let cached = cache_path.as_deref().and_then(CachedFeed::load);
+
+    let mut req = client.get(url);
+    if let Some(cached) = &cached {
+        if let Some(etag) = &cached.etag {
+            req = req.header(reqwest::header::IF_NONE_MATCH, etag.as_str());
+        }
+        if let Some(last_modified) = &cached.last_modified {
+            req = req.header(reqwest::header::IF_MODIFIED_SINCE, last_modified.as_str());
+        }
+    }
+    net::acquire();
+    log::debug!("GET {}", url);
+    let resp = req.send()?;
+    if resp.status() == reqwest::StatusCode::NOT_MODIFIED {
+        if let Some(cached) = cached {
+            log::debug!("{} not modified, reusing the cached feed", url);
+            return Ok(parse_rss(&cached.body));
+        }
+    }
+    if !resp.status().is_success() {
+        log::debug!("{} from {}, treating feed as empty", resp.status(), url);
+        return Ok(vec![]);
+    }
+    let header = |name: reqwest::header::HeaderName| resp.headers().get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+    let (etag, last_modified) = (header(reqwest::header::ETAG), header(reqwest::header::LAST_MODIFIED));
+    let body = String::from_utf8_lossy(&net::read_body(resp, MAX_FEED_BYTES)?).into_owned();
+    let items = parse_rss(&body);
+    // Without validators there's nothing to revalidate against, so nothing worth keeping
+    if let Some(path) = &cache_path {
+        if etag.is_some() || last_modified.is_some() {
+            CachedFeed { etag, last_modified, body }.store(path);
+        }
+    }
+    Ok(items)
+}
+
+/// Parses the `<item>`s of an RSS 2.0 document. Items without a link are dropped, and
+/// parsing stops quietly at the first malformed element, keeping what was read so far.
+/// Prefixed elements such as Bing's `<News:Source>` are matched by local name.
+pub fn parse_rss(xml: &str) -> Vec<RawItem> {
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+
+    let mut items = Vec::new();
+    let mut buf = Vec::new();
+    let mut in_item = false;
+    let mut current = RawItem::default();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) => {
+                let field = match e.local_name().as_ref() {
+                    b"item" => {
+                        in_item = true;
+                        None
+                    }
+                    b"title" if in_item => Some(&mut current.title),
+                    b"link" if in_item => Some(&mut current.link),
+                    b"pubDate" if in_item => Some(&mut current.date),
+                    b"source" | b"Source" if in_item => Some(&mut current.source),
+                    b"description" if in_item => Some(&mut current.description),
+                    _ => None,
+                };
+                if let Some(field) = field {
+                    // read_text hands back the raw escaped text; the description is unescaped below with its markup
+                    match reader.read_text(e.name()) {
+                        Ok(text) if e.local_name().as_ref() == b"description" => *field = text.to_string(),
+                        Ok(text) => *field = unescape(&text).map(|c| c.into_owned()).unwrap_or_else(|_| text.to_string()),
+                        Err(_) => break,
                     }
                 }
-                Ok(Event::Eof) => break,
-                Err(_) => break,
-                _ => (),
             }
-            buf.clear();
+            Ok(Event::End(ref e)) if e.local_name().as_ref() == b"item" => {
+                let mut item = std::mem::take(&mut current);
+                if !item.link.is_empty() {
+                    // CLEANUP DESCRIPTION
+                    // 1. Unescape HTML entities (e.g. &lt; -> <)
+                    let unescaped = unescape(&item.description).map(|c| c.into_owned()).unwrap_or_else(|_| item.description.clone());
+                    // 2. Parse as HTML fragment to strip tags
+                    let frag = Html::parse_fragment(&unescaped);
+                    item.description = frag.root_element().text().collect::<Vec<_>>().join(" ").trim().to_string();
+                    item.date = normalize_pub_date(&item.date);
+                    items.push(item);
+                }
+                in_item = false;
+            }
+            Ok(Event::Eof) => break,
+            Err(_) => break,
+            _ => (),
         }
+        buf.clear();
+    }
+    items
+}
 
-        let mut final_news = Vec::new();
-        
-        let mut headers = HeaderMap::new();
-        headers.insert(ACCEPT, HeaderValue::from_static("text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,*/*;q=0.8"));
-        headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_static("en-US,en;q=0.9"));
-
-        let article_client = reqwest::blocking::Client::builder()
-            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/121.0.0.0 Safari/537.36")
-            .default_headers(headers)
-            .timeout(Duration::from_secs(5)) 
-            .redirect(reqwest::redirect::Policy::limited(10)) 
-            .cookie_store(true)
-            .build()?;
-
-        for (date, title, source, link, desc) in raw_items.into_iter().take(5) { 
-             let mut snippet = scrape_article_body(&article_client, &link).unwrap_or_default();
-             
-             // Check if scrape failed or was rejected
-             if snippet.len() < 50 || snippet.contains("JavaScript is disabled") {
-                 // FALLBACK: Use CLEANED RSS Description
-                 if !desc.is_empty() {
-                     snippet = format!("(Summary): {}", desc);
-                 } else {
-                     snippet = "Content unavailable.".to_string();
-                 }
-             }
-
-             final_news.push(NewsItem {
-                 datetime: date,
-                 headline: title,
-                 source: if source.is_empty() { "Google News".to_string() } else { source },
-                 content_snippet: snippet,
-             });
+/// Token Jaccard similarity above which two headlines are treated as the same story.
+pub const HEADLINE_DEDUP_THRESHOLD: f64 = 0.6;
+
+/// Drops syndicated copies of the same story. Headlines are compared after normalization
+/// (see `normalize_headline`); a match is an identical token set or a Jaccard similarity
+/// above `threshold`. The first occurrence keeps its position, but if it only has the RSS
+/// fallback snippet and a duplicate has a scraped body, the duplicate takes its place.
+pub fn dedup_news(items: Vec<NewsItem>, threshold: f64) -> Vec<NewsItem> {
+    let mut kept: Vec<(HashSet<String>, NewsItem)> = Vec::new();
+    for item in items {
+        let tokens = headline_tokens(&item.headline);
+        match kept.iter_mut().find(|(seen, _)| same_story(seen, &tokens, threshold)) {
+            Some((_, existing)) => {
+                if !has_scraped_body(existing) && has_scraped_body(&item) {
+                    *existing = item;
+                }
+            }
+            None => kept.push((tokens, item)),
         }
+    }
+    kept.into_iter().map(|(_, item)| item).collect()
+}
 
-        Ok(final_news)
+/// Same matching as `dedup_news`, keeping the first occurrence of each story.
+fn dedup_by_headline<T>(items: Vec<T>, headline: impl Fn(&T) -> &str, threshold: f64) -> Vec<T> {
+    let mut seen: Vec<HashSet<String>> = Vec::new();
+    items.into_iter().filter(|item| {
+        let tokens = headline_tokens(headline(item));
+        if seen.iter().any(|s| same_story(s, &tokens, threshold)) {
+            return false;
+        }
+        seen.push(tokens);
+        true
+    }).collect()
+}
+
+fn headline_tokens(headline: &str) -> HashSet<String> {
+    normalize_headline(headline).split_whitespace().map(str::to_string).collect()
+}
+
+fn same_story(a: &HashSet<String>, b: &HashSet<String>, threshold: f64) -> bool {
+    let inter = a.intersection(b).count() as f64;
+    let union = a.union(b).count() as f64;
+    union == 0.0 || a == b || inter / union > threshold
+}
+
+/// Lowercases, drops the trailing " - Source" Google News appends, and strips punctuation.
+fn normalize_headline(headline: &str) -> String {
+    let base = headline.rsplit_once(" - ").map(|(h, _)| h).unwrap_or(headline);
+    base.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c.is_whitespace() { c } else { ' ' })
+        .collect()
+}
+
+fn has_scraped_body(item: &NewsItem) -> bool {
+    !item.content_snippet.starts_with("(Summary): ") && item.content_snippet != "Content unavailable."
+}
+
+impl RssNewsCollector {
+    /// Whether `deadline` has passed, so no further feed or article request should start.
+    fn past_deadline(&self) -> bool {
+        self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+
+    /// Scrapes one article, applying `fallback` when the body is unusable or, with
+    /// `respect_robots`, when the publisher's robots.txt disallows the page. None means skipped.
+    /// Items without a `<source>` are attributed to the publisher's host, else to `feed`.
+    /// With `headlines_only` nothing is fetched and the snippet is the feed's description.
+    fn build_news_item(&self, client: &reqwest::blocking::Client, raw: &RawItem, feed: &str) -> Option<NewsItem> {
+        let link = &raw.link;
+        let decoded = resolve_google_news_url(link).or_else(|| resolve_bing_news_url(link)).unwrap_or_else(|| link.clone());
+        let (mut snippet, url) = if self.headlines_only {
+            let summary = if raw.description.is_empty() { String::new() } else { format!("(Summary): {}", raw.description) };
+            (summary, decoded.clone())
+        } else if self.past_deadline() {
+            log::debug!("deadline reached, not scraping {}", decoded);
+            (String::new(), decoded.clone())
+        } else if self.respect_robots && !robots::allowed(&decoded) {
+            log::debug!("robots.txt disallows {}, using the feed summary", decoded);
+            (String::new(), decoded.clone())
+        } else {
+            scrape_article_body(client, &decoded, self.min_paragraph_len, &self.boilerplate_keywords, &self.content_selectors).unwrap_or_else(|e| {
+                log::debug!("scraping {} failed: {}", decoded, e);
+                (String::new(), decoded.clone())
+            })
+        };
+
+        // Check if scrape failed or was rejected
+        if !self.headlines_only && (snippet.len() < self.min_snippet_len || snippet.contains("JavaScript is disabled")) {
+            snippet = match self.fallback {
+                FallbackPolicy::RssDescription if !raw.description.is_empty() => format!("(Summary): {}", raw.description),
+                FallbackPolicy::RssDescription | FallbackPolicy::MarkUnavailable => "Content unavailable.".to_string(),
+                FallbackPolicy::Skip => {
+                    log::debug!("no usable body for {}, skipping it", url);
+                    return None;
+                }
+            };
+        }
+
+        let source = match raw.source.as_str() {
+            "" => publisher_host(&url).unwrap_or_else(|| feed.to_string()),
+            source => source.to_string(),
+        };
+        // Score the full text so the sentiment doesn't depend on the truncation budget
+        let sentiment = score_sentiment(&format!("{} {}", raw.title, snippet));
+        if let Some(max) = self.snippet_max_sentences {
+            snippet = truncate_sentences(&snippet, max);
+        }
+        if let Some(max) = self.snippet_max_chars {
+            snippet = truncate_snippet(&snippet, max);
+        }
+        Some(NewsItem {
+            datetime: raw.date.clone(),
+            headline: raw.title.clone(),
+            source,
+            url,
+            sentiment,
+            content_snippet: snippet,
+        })
+    }
+}
+
+/// Finance-tuned word weights, keyed by stem; see `SENTIMENT_SUFFIXES` for the inflections matched.
+const SENTIMENT_LEXICON: &[(&str, f32)] = &[
+    ("beat", 1.0), ("surg", 1.5), ("soar", 1.5), ("jump", 1.0), ("rall", 1.0), ("gain", 0.8),
+    ("ris", 0.5), ("rose", 0.5), ("climb", 0.8), ("record", 0.8), ("upgrad", 1.2), ("outperform", 1.0),
+    ("bullish", 1.2), ("strong", 0.8), ("growth", 0.6), ("profit", 0.6), ("boost", 0.8), ("rais", 0.6),
+    ("exceed", 1.0), ("top", 0.5), ("buyback", 0.8), ("approv", 0.8), ("wins", 0.8), ("upbeat", 1.0),
+    ("miss", -1.0), ("plung", -1.5), ("tumbl", -1.2), ("slump", -1.2), ("sink", -1.0), ("sank", -1.0),
+    ("fall", -0.8), ("fell", -0.8), ("drop", -0.8), ("declin", -0.6), ("downgrad", -1.2), ("underperform", -1.0),
+    ("bearish", -1.2), ("weak", -0.8), ("loss", -0.8), ("lawsuit", -1.2), ("su", -1.0), ("prob", -1.0),
+    ("investigat", -1.0), ("investigation", -1.0), ("recall", -1.0), ("fraud", -1.5), ("layoff", -1.0), ("cut", -0.6),
+    ("warn", -1.0), ("bankrupt", -2.0), ("bankruptcy", -2.0), ("default", -1.2), ("delist", -1.5), ("halt", -0.8),
+    ("crash", -1.5),
+];
+
+/// Endings a token may add to a lexicon stem ("surg" matches "surge", "surges", "surged", "surging").
+/// Matching stops there, so "missile" never hits "miss" and "probably" never hits "prob".
+const SENTIMENT_SUFFIXES: &[&str] = &["", "e", "s", "es", "ed", "d", "ing", "y", "ies", "ied", "al"];
+
+/// Tokens that flip the polarity of the next sentiment word ("not beat", "no growth").
+const SENTIMENT_NEGATORS: &[&str] = &["not", "no", "never", "without", "fails", "failed"];
+
+/// Scores `text` against `SENTIMENT_LEXICON`, normalised into [-1, 1] as `s / sqrt(s² + 15)`
+/// so a single hit reads as mild and many agreeing hits approach ±1. Neutral text scores 0.
+pub fn score_sentiment(text: &str) -> f32 {
+    let text = text.to_lowercase();
+    let mut sum = 0.0f32;
+    let mut negate = false;
+    for token in text.split(|c: char| !c.is_alphanumeric()).filter(|t| !t.is_empty()) {
+        if SENTIMENT_NEGATORS.contains(&token) {
+            negate = true;
+            continue;
+        }
+        let hit = SENTIMENT_LEXICON.iter().find(|(stem, _)| {
+            token.strip_prefix(stem).is_some_and(|rest| SENTIMENT_SUFFIXES.contains(&rest))
+        });
+        if let Some((_, weight)) = hit {
+            sum += if negate { -weight } else { *weight };
+        }
+        negate = false;
+    }
+    sum / (sum * sum + 15.0).sqrt()
+}
+
+// Compiled once rather than per article
+static P_SELECTOR: LazyLock<Selector> = LazyLock::new(|| Selector::parse("p").unwrap());
+static A_SELECTOR: LazyLock<Selector> = LazyLock::new(|| Selector::parse("a").unwrap());
+
+/// Compiles a CSS selector for `RssNewsCollector::content_selectors`, e.g. `article p` or
+/// `.article-body p`. Each element it matches is taken as one paragraph.
+pub fn parse_selector(css: &str) -> Result<Selector> {
+    Selector::parse(css).map_err(|e| ScrapyError::InvalidInput(format!("bad CSS selector {:?}: {}", css, e)))
+}
+
+/// Minimum readability score for a container to be trusted as the article body.
+const READABILITY_MIN_SCORE: f64 = 8.0;
+
+/// Most of a feed read (after decompression); anything past it is ignored.
+const MAX_FEED_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Most of an article page read before parsing; anything past it is ignored.
+const MAX_ARTICLE_BYTES: u64 = 2 * 1024 * 1024;
+
+/// Whether a response with these headers can be parsed as an article page. A missing
+/// `Content-Type` is given the benefit of the doubt; PDFs, JSON, images and the like are not.
+fn is_html(headers: &reqwest::header::HeaderMap) -> bool {
+    let Some(value) = headers.get(reqwest::header::CONTENT_TYPE) else { return true };
+    let mime = value.to_str().unwrap_or_default().split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    mime == "text/html" || mime == "application/xhtml+xml"
+}
+
+/// Readability-style extraction: every substantial <p> adds to its parent's score (half to the
+/// grandparent) based on length and comma count, each container is then discounted by its
+/// link density, and the paragraphs of the best container are returned. Returns None when
+/// nothing scores above `READABILITY_MIN_SCORE`.
+fn extract_main_content(document: &Html, min_paragraph_len: usize, boilerplate: &[String]) -> Option<Vec<String>> {
+    let mut scores = HashMap::new();
+    for p in document.select(&P_SELECTOR) {
+        let text = element_text(&p);
+        if text.len() < 25 { continue; }
+        let score = 1.0 + text.matches(',').count() as f64 + (text.len() as f64 / 100.0).min(3.0);
+        if let Some(parent) = p.parent().and_then(ElementRef::wrap) {
+            *scores.entry(parent.id()).or_insert(0.0) += score;
+            if let Some(grand) = parent.parent().and_then(ElementRef::wrap) {
+                *scores.entry(grand.id()).or_insert(0.0) += score / 2.0;
+            }
+        }
+    }
+
+    let (best, score) = scores
+        .into_iter()
+        .filter_map(|(id, score)| {
+            let el = ElementRef::wrap(document.tree.get(id)?)?;
+            let text_len = element_text(&el).len() as f64;
+            if text_len == 0.0 { return None; }
+            // Nav bars and "related articles" lists are mostly link text
+            let link_len = el.select(&A_SELECTOR).map(|a| element_text(&a).len()).sum::<usize>() as f64;
+            Some((el, score * (1.0 - (link_len / text_len).min(1.0))))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+    if score < READABILITY_MIN_SCORE {
+        return None;
+    }
+
+    let paragraphs: Vec<String> = best.select(&P_SELECTOR).filter_map(|el| clean_paragraph(&el, min_paragraph_len, boilerplate)).collect();
+    if paragraphs.is_empty() { None } else { Some(paragraphs) }
+}
+
+fn element_text(el: &ElementRef) -> String {
+    el.text().collect::<Vec<_>>().join(" ").trim().to_string()
+}
+
+/// Keeps paragraphs that are long enough and contain none of the `boilerplate` keywords.
+fn clean_paragraph(el: &ElementRef, min_len: usize, boilerplate: &[String]) -> Option<String> {
+    let clean_text = element_text(el);
+
+    if clean_text.len() < min_len { return None; } 
+    
+    let lower = clean_text.to_lowercase();
+    if boilerplate.iter().any(|k| lower.contains(k.as_str())) {
+        return None;
+    }
+    
+    Some(clean_text)
+}
+
+/// Byte offsets just past each sentence-ending `.`, `!` or `?` that is followed by whitespace
+/// or ends the text.
+fn sentence_ends(text: &str) -> impl Iterator<Item = usize> + '_ {
+    let mut chars = text.char_indices().peekable();
+    std::iter::from_fn(move || {
+        while let Some((i, c)) = chars.next() {
+            if matches!(c, '.' | '!' | '?') && chars.peek().is_none_or(|(_, next)| next.is_whitespace()) {
+                return Some(i + c.len_utf8());
+            }
+        }
+        None
+    })
+}
+
+/// Cuts `text` to at most `max_chars` characters plus an ellipsis, ending on the last sentence
+/// boundary in the second half of the budget, else on a word boundary. Text that fits is
+/// returned unchanged.
+pub fn truncate_snippet(text: &str, max_chars: usize) -> String {
+    let Some((cut, _)) = text.char_indices().nth(max_chars) else { return text.to_string() };
+    if text[cut..].trim().is_empty() {
+        return text.to_string();
     }
+    let head = &text[..cut];
+    let sentence = sentence_ends(head).last().filter(|&end| head[..end].chars().count() * 2 >= max_chars);
+    match sentence {
+        Some(end) => format!("{} …", &head[..end]),
+        None => {
+            let end = head.rfind(char::is_whitespace).unwrap_or(head.len());
+            format!("{}…", head[..end].trim_end())
+        }
+    }
+}
+
+/// Keeps the first `max_sentences` sentences of `text`, adding an ellipsis if anything was cut.
+pub fn truncate_sentences(text: &str, max_sentences: usize) -> String {
+    match sentence_ends(text).nth(max_sentences.saturating_sub(1)) {
+        Some(end) if !text[end..].trim().is_empty() => format!("{} …", &text[..end]),
+        _ => text.to_string(),
+    }
+}
+
+/// Converts an RSS pubDate (RFC 2822, e.g. "Mon, 12 Feb 2024 14:03:00 GMT") to UTC RFC3339.
+/// Unparseable dates are passed through unchanged.
+fn normalize_pub_date(raw: &str) -> String {
+    match chrono::DateTime::parse_from_rfc2822(raw.trim()) {
+        Ok(dt) => dt.with_timezone(&chrono::Utc).to_rfc3339(),
+        Err(_) => raw.to_string(),
+    }
+}
+
+/// Decodes the publisher URL embedded in a news.google.com `/articles/<id>` link. The id is
+/// URL-safe base64 of a small protobuf that carries the target URL as a plain string.
+/// Returns None for other links and for newer opaque ids that need a Google round-trip.
+fn resolve_google_news_url(link: &str) -> Option<String> {
+    let parsed = reqwest::Url::parse(link).ok()?;
+    if parsed.host_str() != Some("news.google.com") {
+        return None;
+    }
+    let mut segments = parsed.path_segments()?;
+    segments.find(|s| *s == "articles")?;
+    let id = segments.next()?;
+    let bytes = URL_SAFE_NO_PAD.decode(id.trim_end_matches('=')).ok()?;
+    let start = bytes.windows(4).position(|w| w == b"http")?;
+    // The string is length-prefixed by a 1- or 2-byte varint right before it
+    let len = match (start.checked_sub(2).map(|i| bytes[i]), bytes.get(start.checked_sub(1)?)) {
+        (Some(lo), Some(&hi)) if lo & 0x80 != 0 && hi & 0x80 == 0 => (lo & 0x7f) as usize | (hi as usize) << 7,
+        (_, Some(&len)) if len & 0x80 == 0 => len as usize,
+        _ => return None,
+    };
+    let url = std::str::from_utf8(bytes.get(start..start + len)?).ok()?;
+    reqwest::Url::parse(url).ok().map(|u| u.to_string())
+}
+
+/// Host of a publisher URL without `www.`, e.g. `reuters.com`. None for the aggregators' own
+/// links, which say nothing about who wrote the story.
+fn publisher_host(url: &str) -> Option<String> {
+    let host = reqwest::Url::parse(url).ok()?.host_str()?.trim_start_matches("www.").to_string();
+    let aggregator = ["news.google.com", "bing.com", "finance.yahoo.com"].iter().any(|a| host == *a || host.ends_with(&format!(".{}", a)));
+    (!aggregator).then_some(host)
+}
+
+/// Bing wraps publisher links in an `apiclick.aspx` redirect carrying the target in `url=`.
+fn resolve_bing_news_url(link: &str) -> Option<String> {
+    let parsed = reqwest::Url::parse(link).ok()?;
+    if !parsed.host_str()?.ends_with("bing.com") {
+        return None;
+    }
+    parsed.query_pairs().find(|(k, _)| k == "url").map(|(_, v)| v.into_owned())
 }
 
-fn scrape_article_body(client: &reqwest::blocking::Client, url: &str) -> Result<String> {
-    if url.contains("google.com/search") { return Ok("Skipped search link".to_string()); }
+/// Returns the snippet and the final URL after redirects.
+fn scrape_article_body(client: &reqwest::blocking::Client, url: &str, min_paragraph_len: usize, boilerplate: &[String], selectors: &[Selector]) -> Result<(String, String)> {
+    if url.contains("google.com/search") { return Ok(("Skipped search link".to_string(), url.to_string())); }
 
+    net::acquire();
+    log::debug!("GET {}", url);
     let resp = client.get(url).send()?;
+    let final_url = resp.url().to_string();
     if !resp.status().is_success() {
-        return Ok(String::new());
+        return Ok((String::new(), final_url));
     }
-    let html = resp.text()?;
+    // An empty body sends the item down the `fallback` path
+    if !is_html(resp.headers()) {
+        log::debug!("{} is not HTML ({:?}), not scraping it", final_url, resp.headers().get(reqwest::header::CONTENT_TYPE));
+        return Ok((String::new(), final_url));
+    }
+    let bytes = net::read_body(resp, MAX_ARTICLE_BYTES)?;
+    let html = String::from_utf8_lossy(&bytes);
     let document = Html::parse_document(&html);
     
-    let p_selector = Selector::parse("p").unwrap();
-    let paragraphs: Vec<String> = document.select(&p_selector)
-        .filter_map(|el| {
-            let text = el.text().collect::<Vec<_>>().join(" ");
-            let clean_text = text.trim();
-
-            if clean_text.len() < 50 { return None; } 
-            
-            let lower = clean_text.to_lowercase();
-            if lower.contains("cookie") || 
-               lower.contains("subscribe") || 
-               lower.contains("rights reserved") ||
-               lower.contains("click here") ||
-               lower.contains("javascript") ||
-               lower.contains("adblock") ||
-               lower.contains("promo") {
-                return None;
-            }
-            
-            Some(clean_text.to_string())
-        })
-        .collect();
+    // The first configured selector that finds anything wins; then the densest content block;
+    // then every acceptable <p> on the page
+    let select = |selector: &Selector| -> Vec<String> {
+        document.select(selector).filter_map(|el| clean_paragraph(&el, min_paragraph_len, boilerplate)).collect()
+    };
+    let paragraphs = selectors
+        .iter()
+        .map(select)
+        .find(|paragraphs| !paragraphs.is_empty())
+        .or_else(|| extract_main_content(&document, min_paragraph_len, boilerplate))
+        .unwrap_or_else(|| select(&P_SELECTOR));
 
     if paragraphs.is_empty() {
-        return Ok(String::new());
+        return Ok((String::new(), final_url));
     }
 
     let mut result = String::new();
-    let mut seen = std::collections::HashSet::new();
+    let mut seen = HashSet::new();
     let mut count = 0;
     
     for p in &paragraphs {
@@ -185,105 +848,286 @@ fn scrape_article_body(client: &reqwest::blocking::Client, url: &str) -> Result<
         }
     }
 
-    Ok(result)
+    Ok((result, final_url))
 }
 
 // ... Rest unchanged ...
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct InsiderEvent { pub date: String, pub entity_name: String, pub relation: String, pub transaction_type: String, pub value_approx: String }
-#[derive(Debug, Clone)]
-pub struct InstitutionalEvent { pub holder_name: String, pub pct_held: String }
+/// Which Yahoo ownership list a holder came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum HolderType { Institution, Fund }
+impl HolderType {
+    pub fn label(self) -> &'static str {
+        match self { HolderType::Institution => "Institution", HolderType::Fund => "Fund" }
+    }
+}
+#[derive(Debug, Clone, Serialize)]
+pub struct InstitutionalEvent {
+    pub holder_name: String,
+    pub holder_type: HolderType,
+    pub pct_held: String, // as Yahoo formats it, e.g. "8.97%"
+    pub pct_held_raw: Option<f64>, // fraction held, e.g. 0.0897; orders the list
+}
 pub trait InsiderCollector {
     fn collect_activity(&self, ticker: &str, window_days: i64) -> Result<(Vec<InsiderEvent>, Vec<InstitutionalEvent>)>;
 }
 pub struct YahooInsiderCollector;
 #[derive(Deserialize, Debug)]
-struct QSumResponse { quoteSummary: QSumResult }
+#[serde(rename_all = "camelCase")]
+struct QSumResponse { quote_summary: QSumResult }
 #[derive(Deserialize, Debug)]
-struct QSumResult { result: Option<Vec<QSumModules>>, error: Option<serde_json::Value> }
+struct QSumResult { result: Option<Vec<QSumModules>> }
 #[derive(Deserialize, Debug)]
-struct QSumModules { insiderTransactions: Option<InsiderTxModule>, institutionOwnership: Option<OwnershipModule>, fundOwnership: Option<OwnershipModule> }
+#[serde(rename_all = "camelCase")]
+struct QSumModules { insider_transactions: Option<InsiderTxModule>, institution_ownership: Option<OwnershipModule>, fund_ownership: Option<OwnershipModule>, summary_detail: Option<SummaryDetailModule>, price: Option<PriceModule> }
 #[derive(Deserialize, Debug)]
 struct InsiderTxModule { transactions: Vec<InsiderTx> }
 #[derive(Deserialize, Debug)]
-struct InsiderTx { filerName: Option<String>, filerRelation: Option<String>, transactionText: Option<String>, startDate: Option<FmtDate>, value: Option<FmtValue> }
+#[serde(rename_all = "camelCase")]
+struct InsiderTx { filer_name: Option<String>, filer_relation: Option<String>, transaction_text: Option<String>, start_date: Option<FmtDate>, value: Option<FmtValue> }
 #[derive(Deserialize, Debug)]
-struct OwnershipModule { ownershipList: Vec<OwnerEntry> }
+#[serde(rename_all = "camelCase")]
+struct OwnershipModule { ownership_list: Vec<OwnerEntry> }
 #[derive(Deserialize, Debug)]
-struct OwnerEntry { organization: Option<String>, pctHeld: Option<FmtValue> }
+#[serde(rename_all = "camelCase")]
+struct OwnerEntry { organization: Option<String>, pct_held: Option<FmtValue> }
 #[derive(Deserialize, Debug)]
 struct FmtDate { fmt: Option<String> }
 #[derive(Deserialize, Debug)]
 struct FmtValue { fmt: Option<String>, raw: Option<f64> }
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SummaryDetailModule { market_cap: Option<FmtValue>, #[serde(rename = "trailingPE")] trailing_pe: Option<FmtValue> }
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct PriceModule { market_cap: Option<FmtValue> }
 impl InsiderCollector for YahooInsiderCollector {
     fn collect_activity(&self, ticker: &str, window_days: i64) -> Result<(Vec<InsiderEvent>, Vec<InstitutionalEvent>)> {
         let url = format!("https://query2.finance.yahoo.com/v10/finance/quoteSummary/{}?modules=insiderTransactions,institutionOwnership,fundOwnership", ticker);
-        let client = reqwest::blocking::Client::builder()
-            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/121.0.0.0 Safari/537.36")
-            .build()?;
+        let client = net::build_client(ClientOpts::default())?;
+        net::acquire();
+        log::debug!("GET {}", url);
         let resp = client.get(&url).send()?;
         if !resp.status().is_success() { return Ok((vec![], vec![])); }
         let text = resp.text()?;
-        let data: QSumResponse = serde_json::from_str(&text).unwrap_or(QSumResponse { quoteSummary: QSumResult { result: None, error: None } });
+        let data: QSumResponse = serde_json::from_str(&text).unwrap_or(QSumResponse { quote_summary: QSumResult { result: None } });
         let mut trades = Vec::new();
         let mut holders = Vec::new();
         let cutoff_date = chrono::Utc::now().naive_utc().date() - chrono::Duration::days(window_days);
-        if let Some(res_list) = data.quoteSummary.result {
+        if let Some(res_list) = data.quote_summary.result {
             if let Some(modules) = res_list.first() {
-                if let Some(tx_mod) = &modules.insiderTransactions {
+                if let Some(tx_mod) = &modules.insider_transactions {
                     for tx in &tx_mod.transactions {
-                        let date_str = tx.startDate.as_ref().and_then(|d| d.fmt.clone()).unwrap_or_default();
+                        let date_str = tx.start_date.as_ref().and_then(|d| d.fmt.clone()).unwrap_or_default();
                         let include = if date_str.is_empty() { false } else {
                             if let Ok(d) = chrono::NaiveDate::parse_from_str(&date_str, "%Y-%m-%d") { d >= cutoff_date } else { false }
                         };
                         if include {
                             trades.push(InsiderEvent {
                                 date: date_str,
-                                entity_name: tx.filerName.clone().unwrap_or("Unknown".to_string()),
-                                relation: tx.filerRelation.clone().unwrap_or("Insider".to_string()),
-                                transaction_type: tx.transactionText.clone().unwrap_or("Trade".to_string()),
+                                entity_name: tx.filer_name.clone().unwrap_or("Unknown".to_string()),
+                                relation: tx.filer_relation.clone().unwrap_or("Insider".to_string()),
+                                transaction_type: tx.transaction_text.clone().unwrap_or("Trade".to_string()),
                                 value_approx: tx.value.as_ref().and_then(|v| v.fmt.clone()).unwrap_or("0".to_string()),
                             });
                         }
                     }
                 }
-                if let Some(inst) = &modules.institutionOwnership {
-                    for own in inst.ownershipList.iter().take(5) {
-                         holders.push(InstitutionalEvent {
-                             holder_name: own.organization.clone().unwrap_or("Unknown".to_string()),
-                             pct_held: own.pctHeld.as_ref().and_then(|v| v.fmt.clone()).unwrap_or("0%".to_string()),
-                         });
-                    }
-                }
-                if let Some(fund) = &modules.fundOwnership {
-                    for own in fund.ownershipList.iter().take(5) {
+                let lists = [(&modules.institution_ownership, HolderType::Institution, "Unknown"), (&modules.fund_ownership, HolderType::Fund, "Unknown Fund")];
+                for (list, holder_type, unknown) in lists {
+                    let Some(list) = list else { continue };
+                    for own in list.ownership_list.iter().take(5) {
                          holders.push(InstitutionalEvent {
-                             holder_name: own.organization.clone().unwrap_or("Unknown Fund".to_string()),
-                             pct_held: own.pctHeld.as_ref().and_then(|v| v.fmt.clone()).unwrap_or("0%".to_string()),
+                             holder_name: own.organization.clone().unwrap_or(unknown.to_string()),
+                             holder_type,
+                             pct_held: own.pct_held.as_ref().and_then(|v| v.fmt.clone()).unwrap_or("0%".to_string()),
+                             pct_held_raw: own.pct_held.as_ref().and_then(|v| v.raw),
                          });
                     }
                 }
             }
         }
+        // Largest stake first across both lists; holders without a raw figure go last, and
+        // ties keep source order (institutions before funds)
+        holders.sort_by(|a, b| match (a.pct_held_raw, b.pct_held_raw) {
+            (Some(x), Some(y)) => y.total_cmp(&x),
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        });
         Ok((trades, holders))
     }
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+pub struct SenateItem {
+    pub date: String,
+    pub chamber: String,
+    pub member_name: String,
+    pub activity_type: String,
+    pub notes: String, // amount as disclosed, e.g. "$1,001 - $15,000"
+    pub amount_min: Option<f64>,
+    pub amount_max: Option<f64>, // None for the open-ended top bucket
+}
+pub trait SenateCollector {
+    fn collect_senate(&self, ticker: &str, window_days: i64) -> Result<Vec<SenateItem>>;
+}
+/// Dollar bounds of a disclosure amount bucket such as "$1,001 - $15,000". The open-ended
+/// top bucket ("Over $50,000,000", also written "$50,000,000 +") has an infinite maximum, and a
+/// single figure is both bounds. None for "Unknown" and anything else unparseable.
+pub fn parse_amount_range(s: &str) -> Option<(f64, f64)> {
+    let dollars = |v: &str| v.trim().trim_start_matches('$').replace(',', "").trim().parse::<f64>().ok();
+    let s = s.trim();
+    if let Some(min) = s.strip_prefix("Over ").or_else(|| s.strip_suffix('+')) {
+        return Some((dollars(min)?, f64::INFINITY));
+    }
+    match s.split_once(" - ").or_else(|| s.split_once('-')) {
+        Some((min, max)) => Some((dollars(min)?, dollars(max)?)),
+        None => dollars(s).map(|v| (v, v)),
+    }
+}
+
+/// Public Senate periodic transaction reports aggregated by the senate-stock-watcher project.
+pub struct SenateStockWatcherCollector;
+const SENATE_WATCHER_URL: &str = "https://senate-stock-watcher-data.s3-us-west-2.amazonaws.com/aggregate/all_transactions.json";
+#[derive(Deserialize, Debug)]
+struct SenateTx { transaction_date: Option<String>, ticker: Option<String>, senator: Option<String>, #[serde(rename = "type")] tx_type: Option<String>, amount: Option<String> }
+impl SenateCollector for SenateStockWatcherCollector {
+    fn collect_senate(&self, ticker: &str, window_days: i64) -> Result<Vec<SenateItem>> {
+        let client = net::build_client(ClientOpts::with_timeout(20))?;
+        // The disclosures are optional context: any network/format problem just means no rows
+        net::acquire();
+        log::debug!("GET {}", SENATE_WATCHER_URL);
+        let txs: Vec<SenateTx> = match client.get(SENATE_WATCHER_URL).send() {
+            Ok(resp) if resp.status().is_success() => resp.json().unwrap_or_default(),
+            _ => return Ok(vec![]),
+        };
+        let cutoff_date = chrono::Utc::now().naive_utc().date() - chrono::Duration::days(window_days);
+        let mut items = Vec::new();
+        for tx in txs {
+            // Tickers are sometimes wrapped in an <a> tag linking to the quote page
+            let tx_ticker = tx.ticker.as_deref().map(|t| Html::parse_fragment(t).root_element().text().collect::<String>()).unwrap_or_default();
+            if !tx_ticker.trim().eq_ignore_ascii_case(ticker) { continue; }
+            let date = match tx.transaction_date.as_deref().and_then(|d| chrono::NaiveDate::parse_from_str(d, "%m/%d/%Y").ok()) {
+                Some(d) if d >= cutoff_date => d,
+                _ => continue,
+            };
+            let raw_type = tx.tx_type.unwrap_or_default();
+            let lower = raw_type.to_lowercase();
+            let activity_type = if lower.starts_with("purchase") { "buy".to_string() } else if lower.starts_with("sale") { "sell".to_string() } else { lower };
+            let notes = tx.amount.unwrap_or_default();
+            let bounds = parse_amount_range(&notes);
+            items.push(SenateItem {
+                date: date.format("%Y-%m-%d").to_string(),
+                chamber: "Senate".to_string(),
+                member_name: tx.senator.unwrap_or("Unknown".to_string()),
+                activity_type,
+                amount_min: bounds.map(|(min, _)| min),
+                amount_max: bounds.map(|(_, max)| max).filter(|max| max.is_finite()),
+                notes,
+            });
+        }
+        items.sort_by(|a, b| b.date.cmp(&a.date));
+        Ok(items)
+    }
+}
+#[derive(Debug, Clone, Serialize)]
 pub struct FinanceSnapshot { pub source: String, pub asof_utc: String, pub price_last: f64, pub market_cap_approx: Option<f64>, pub pe_ratio_approx: Option<f64>, pub notes: String }
-pub trait FinanceSnapshotCollector { fn collect_snapshot(&self, ticker: &str, meta: Option<&crate::fetcher::YahooMeta>) -> Result<Option<FinanceSnapshot>>; }
+pub trait FinanceSnapshotCollector { fn collect_snapshot(&self, ticker: &str, meta: Option<&crate::provider::ProviderMeta>) -> Result<Option<FinanceSnapshot>>; }
 pub struct YahooSnapshotCollector;
 impl FinanceSnapshotCollector for YahooSnapshotCollector {
-    fn collect_snapshot(&self, _ticker: &str, meta: Option<&crate::fetcher::YahooMeta>) -> Result<Option<FinanceSnapshot>> {
+    fn collect_snapshot(&self, _ticker: &str, meta: Option<&crate::provider::ProviderMeta>) -> Result<Option<FinanceSnapshot>> {
         if let Some(m) = meta {
+            // Valuation is best-effort on top of the chart price
+            let (market_cap, pe, valuation_note) = match fetch_valuation(&m.symbol) {
+                Ok((mc, pe)) => {
+                    let state = |v: Option<f64>| if v.is_some() { "fetched" } else { "missing" };
+                    (mc, pe, format!("market_cap: {}, pe: {}", state(mc), state(pe)))
+                }
+                Err(e) => (None, None, format!("valuation unavailable: {}", e)),
+            };
             return Ok(Some(FinanceSnapshot {
-                source: "YahooChartMeta".to_string(),
-                asof_utc: chrono::Utc::now().to_rfc3339(),
-                price_last: m.regularMarketPrice.or(m.chartPreviousClose).unwrap_or(0.0),
-                market_cap_approx: None,
-                pe_ratio_approx: None,
-                notes: format!("Currency: {}, Symbol: {}", m.currency.clone().unwrap_or_default(), m.symbol),
+                source: m.source.clone(),
+                // The quote's own time, so it lines up with the bars; scrape time only as a fallback
+                asof_utc: m.market_time.unwrap_or_else(chrono::Utc::now).to_rfc3339(),
+                price_last: m.regular_market_price.or(m.previous_close).unwrap_or(0.0),
+                market_cap_approx: market_cap,
+                pe_ratio_approx: pe,
+                notes: format!("Currency: {}, Symbol: {}, {}", m.currency.clone().unwrap_or_default(), m.symbol, valuation_note),
             }));
         }
         Ok(None)
     }
 }
+
+/// Market cap and trailing P/E from the quoteSummary summaryDetail/defaultKeyStatistics/price modules.
+fn fetch_valuation(ticker: &str) -> Result<(Option<f64>, Option<f64>)> {
+    let url = format!("https://query2.finance.yahoo.com/v10/finance/quoteSummary/{}?modules=summaryDetail,defaultKeyStatistics,price", ticker);
+    let client = net::build_client(ClientOpts::with_timeout(8))?;
+    net::acquire();
+    log::debug!("GET {}", url);
+    let resp = client.get(&url).send()?;
+    if !resp.status().is_success() { return Err(ScrapyError::Http(resp.status())); }
+    let data: QSumResponse = resp.json()?;
+    let modules = data.quote_summary.result.and_then(|r| r.into_iter().next()).ok_or(ScrapyError::NoData)?;
+    let raw = |v: &Option<FmtValue>| v.as_ref().and_then(|v| v.raw);
+    let market_cap = modules.price.as_ref().and_then(|p| raw(&p.market_cap))
+        .or_else(|| modules.summary_detail.as_ref().and_then(|d| raw(&d.market_cap)));
+    let pe = modules.summary_detail.as_ref().and_then(|d| raw(&d.trailing_pe));
+    Ok((market_cap, pe))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc2822_pub_date_becomes_utc_rfc3339() {
+        assert_eq!(normalize_pub_date("Mon, 12 Feb 2024 14:03:00 GMT"), "2024-02-12T14:03:00+00:00");
+        assert_eq!(normalize_pub_date("Mon, 12 Feb 2024 09:03:00 -0500"), "2024-02-12T14:03:00+00:00");
+        // Unparseable dates are kept as given
+        assert_eq!(normalize_pub_date("yesterday"), "yesterday");
+    }
+
+    fn content_type(value: &str) -> reqwest::header::HeaderMap {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(reqwest::header::CONTENT_TYPE, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn non_html_content_types_are_not_parsed() {
+        assert!(!is_html(&content_type("application/pdf")));
+        assert!(!is_html(&content_type("application/json; charset=utf-8")));
+        assert!(is_html(&content_type("text/html; charset=UTF-8")));
+        assert!(is_html(&content_type("application/xhtml+xml")));
+    }
+
+    #[test]
+    fn missing_content_type_is_parsed() {
+        assert!(is_html(&reqwest::header::HeaderMap::new()));
+    }
+
+    #[test]
+    fn builder_sets_options_and_derives_ceid() {
+        let collector = RssNewsCollector::builder()
+            .max_items(5)
+            .article_timeout(Duration::from_secs(2))
+            .boilerplate_keywords(vec!["Newsletter".to_string()])
+            .hl("en-GB")
+            .gl("GB")
+            .build();
+        assert_eq!(collector.max_items, 5);
+        assert_eq!(collector.article_timeout, Duration::from_secs(2));
+        assert_eq!(collector.boilerplate_keywords, ["newsletter"]);
+        assert_eq!(collector.locale, NewsLocale { hl: "en-GB".into(), gl: "GB".into(), ceid: "GB:en".into() });
+    }
+
+    #[test]
+    fn builder_defaults_match_default() {
+        let (built, default) = (RssNewsCollector::builder().build(), RssNewsCollector::default());
+        assert_eq!(built.max_items, default.max_items);
+        assert_eq!(built.timeout, default.timeout);
+        assert_eq!(built.article_timeout, default.article_timeout);
+        assert_eq!(built.boilerplate_keywords, default.boilerplate_keywords);
+        assert_eq!(built.locale, default.locale);
+    }
+}
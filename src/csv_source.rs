@@ -0,0 +1,293 @@
+use crate::error::{Result, ScrapyError};
+use crate::market::{CorporateAction, MinuteBar};
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// `--source-path` value that reads the CSV from standard input.
+pub const STDIN_PATH: &str = "-";
+
+/// Column overrides for CSV sources: a header name, or a 0-based column index.
+/// Unset columns are looked up by their standard header name (`ts,o,h,l,c,v`).
+#[derive(Debug, Clone, Default)]
+pub struct ColumnMap {
+    pub ts: Option<String>,
+    pub open: Option<String>,
+    pub high: Option<String>,
+    pub low: Option<String>,
+    pub close: Option<String>,
+    pub vol: Option<String>,
+}
+
+/// Column positions of a minute-bar CSV, resolved from its header row.
+pub struct CsvColumns {
+    pub ts: usize,
+    pub o: usize,
+    pub h: usize,
+    pub l: usize,
+    pub c: usize,
+    pub v: usize,
+    pub adj_c: Option<usize>,
+}
+
+impl CsvColumns {
+    /// Explicit mappings in `overrides` win; otherwise the standard `ts,o,h,l,c,v` header names are used.
+    pub fn from_header(headers: &csv::StringRecord, overrides: &ColumnMap) -> Result<Self> {
+        let joined = headers.iter().collect::<Vec<_>>().join(",");
+        let find = |name: &str| headers.iter().position(|h| h.trim().eq_ignore_ascii_case(name));
+        let require = |name: &str, explicit: &Option<String>| match explicit {
+            Some(spec) => match spec.parse::<usize>() {
+                Ok(idx) if idx < headers.len() => Ok(idx),
+                Ok(idx) => Err(ScrapyError::CsvParse(format!(
+                    "column index {} for '{}' is out of range for header '{}'", idx, name, joined,
+                ))),
+                Err(_) => find(spec).ok_or_else(|| ScrapyError::CsvParse(format!(
+                    "column '{}' (mapped to '{}') not found in header '{}'", spec, name, joined,
+                ))),
+            },
+            None => find(name).ok_or_else(|| ScrapyError::CsvParse(format!(
+                "missing required column '{}' in header '{}' (need ts,o,h,l,c,v or explicit column mappings)", name, joined,
+            ))),
+        };
+        Ok(Self {
+            ts: require("ts", &overrides.ts)?,
+            o: require("o", &overrides.open)?,
+            h: require("h", &overrides.high)?,
+            l: require("l", &overrides.low)?,
+            c: require("c", &overrides.close)?,
+            v: require("v", &overrides.vol)?,
+            adj_c: find("adj_c"),
+        })
+    }
+}
+
+/// Naive layouts seen in vendor exports, read as wall-clock time in the assumed timezone.
+const NAIVE_TS_FORMATS: &[&str] = &["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M", "%Y/%m/%d %H:%M:%S", "%m/%d/%Y %H:%M"];
+
+/// Accepts RFC3339, epoch seconds or milliseconds, or one of `NAIVE_TS_FORMATS`
+/// interpreted in `assume_tz`.
+pub fn parse_timestamp(raw: &str, assume_tz: Tz) -> Result<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(raw) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    if let Ok(n) = raw.parse::<i64>() {
+        // Anything past ~5138 AD in seconds is really milliseconds
+        let dt = if n.abs() >= 100_000_000_000 { DateTime::from_timestamp_millis(n) } else { DateTime::from_timestamp(n, 0) };
+        return dt.ok_or_else(|| ScrapyError::CsvParse(format!("timestamp out of range: '{}'", raw)));
+    }
+    for fmt in NAIVE_TS_FORMATS {
+        if let Ok(naive) = chrono::NaiveDateTime::parse_from_str(raw, fmt) {
+            // Fall-back DST overlaps take the first occurrence; spring-forward gaps don't exist
+            return assume_tz.from_local_datetime(&naive).earliest()
+                .map(|dt| dt.with_timezone(&Utc))
+                .ok_or_else(|| ScrapyError::CsvParse(format!("'{}' does not exist in {}", raw, assume_tz.name())));
+        }
+    }
+    Err(ScrapyError::CsvParse(format!("unrecognized timestamp '{}'", raw)))
+}
+
+/// Parses one data record; `record_no` is 1-based and excludes the header.
+pub fn parse_row(record: &csv::StringRecord, cols: &CsvColumns, record_no: usize, assume_tz: Tz) -> Result<MinuteBar> {
+    let field = |idx: usize, name: &str| record.get(idx).ok_or_else(|| ScrapyError::CsvParse(format!(
+        "record {}: missing '{}' field ({} fields)", record_no, name, record.len(),
+    )));
+    let bad = |name: &str, raw: &str| ScrapyError::CsvParse(format!("record {}: bad {} value '{}'", record_no, name, raw));
+    let price = |idx: usize, name: &str| -> Result<f64> {
+        let raw = field(idx, name)?;
+        raw.parse().map_err(|_| bad(name, raw))
+    };
+
+    let raw_ts = field(cols.ts, "ts")?;
+    let ts_utc = parse_timestamp(raw_ts, assume_tz).map_err(|_| bad("ts", raw_ts))?;
+    let raw_v = field(cols.v, "v")?;
+    let adj_c = match cols.adj_c.map(|idx| field(idx, "adj_c")).transpose()? {
+        Some(raw) if !raw.is_empty() => Some(raw.parse().map_err(|_| bad("adj_c", raw))?),
+        _ => None,
+    };
+
+    Ok(MinuteBar {
+        ts_utc,
+        o: price(cols.o, "o")?,
+        h: price(cols.h, "h")?,
+        l: price(cols.l, "l")?,
+        c: price(cols.c, "c")?,
+        v: raw_v.parse().map_err(|_| bad("v", raw_v))?,
+        adj_c,
+    })
+}
+
+/// Loads minute bars from a CSV with a `ts,o,h,l,c,v[,adj_c]` header (any column order).
+pub fn load_csv_bars(path: &str, assume_tz: Tz, columns: &ColumnMap) -> Result<Vec<MinuteBar>> {
+    let mut rows = Vec::with_capacity(50_000);
+    for_each_csv_bar(path, assume_tz, columns, |_, bar| {
+        rows.push(bar);
+        Ok(())
+    })?;
+    let unsorted = rows.windows(2).filter(|w| w[1].ts_utc < w[0].ts_utc).count();
+    if unsorted > 0 {
+        log::debug!("{}: {} record(s) earlier than the row before them; sorting", path, unsorted);
+    }
+    rows.sort_by_key(|b| b.ts_utc);
+    Ok(rows)
+}
+
+/// `load_csv_bars` for files that should already be in time order: skips the sort and fails
+/// at the first record earlier than the one before it. Equal timestamps are fine (duplicates
+/// are collapsed later).
+pub fn load_csv_bars_presorted(path: &str, assume_tz: Tz, columns: &ColumnMap) -> Result<Vec<MinuteBar>> {
+    let mut rows: Vec<MinuteBar> = Vec::with_capacity(50_000);
+    for_each_csv_bar(path, assume_tz, columns, |record_no, bar| {
+        if let Some(prev) = rows.last().filter(|prev| bar.ts_utc < prev.ts_utc) {
+            return Err(ScrapyError::CsvParse(format!(
+                "record {}: timestamp {} is earlier than the row before it ({}) but the input was assumed sorted",
+                record_no, bar.ts_utc.to_rfc3339(), prev.ts_utc.to_rfc3339())));
+        }
+        rows.push(bar);
+        Ok(())
+    })?;
+    Ok(rows)
+}
+
+/// Parses `path` row by row in file order, handing each bar and its 1-based record number
+/// to `f` without holding the file in memory. Stops at the first error from parsing or `f`.
+/// A `path` of `-` reads standard input.
+pub fn for_each_csv_bar(path: &str, assume_tz: Tz, columns: &ColumnMap, mut f: impl FnMut(usize, MinuteBar) -> Result<()>) -> Result<()> {
+    let input: Box<dyn std::io::Read> = if path == STDIN_PATH {
+        Box::new(std::io::stdin().lock())
+    } else {
+        Box::new(std::fs::File::open(path).map_err(|e| ScrapyError::CsvParse(format!("{}: {}", path, e)))?)
+    };
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(input);
+    let headers = rdr.headers().map_err(|e| ScrapyError::CsvParse(format!("{}: {}", path, e)))?;
+    // A blank file has no header either; that's no rows rather than a missing column
+    if headers.is_empty() {
+        return Ok(());
+    }
+    let cols = CsvColumns::from_header(headers, columns)?;
+
+    let mut record = csv::StringRecord::new();
+    let mut record_no = 0;
+    loop {
+        match rdr.read_record(&mut record) {
+            Ok(true) => {}
+            Ok(false) => return Ok(()),
+            Err(e) => return Err(ScrapyError::CsvParse(format!("record {}: {}", record_no + 1, e))),
+        }
+        record_no += 1;
+        f(record_no, parse_row(&record, &cols, record_no, assume_tz)?)?;
+    }
+}
+
+/// Writes `bars` with a `ts,o,h,l,c,v` header (plus `adj_c` when any bar has one) that
+/// `load_csv_bars` reads back unchanged: UTC RFC3339 timestamps and round-trip float
+/// formatting. Creates missing parent directories.
+pub fn write_minute_csv(path: &str, bars: &[MinuteBar]) -> Result<()> {
+    if let Some(parent) = std::path::Path::new(path).parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let with_adj = bars.iter().any(|b| b.adj_c.is_some());
+    let mut wtr = csv::Writer::from_path(path).map_err(std::io::Error::from)?;
+    let mut header = vec!["ts", "o", "h", "l", "c", "v"];
+    if with_adj {
+        header.push("adj_c");
+    }
+    wtr.write_record(&header).map_err(std::io::Error::from)?;
+    for b in bars {
+        let mut record = vec![
+            b.ts_utc.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            b.o.to_string(),
+            b.h.to_string(),
+            b.l.to_string(),
+            b.c.to_string(),
+            b.v.to_string(),
+        ];
+        if with_adj {
+            record.push(b.adj_c.map(|a| a.to_string()).unwrap_or_default());
+        }
+        wtr.write_record(&record).map_err(std::io::Error::from)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Loads corporate actions from a CSV with an `ex_date` column (YYYY-MM-DD) and optional
+/// `split_ratio` (Default: 1) and `dividend` (Default: 0) columns.
+pub fn load_actions(path: &str) -> Result<Vec<CorporateAction>> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_path(path)
+        .map_err(|e| ScrapyError::CsvParse(format!("{}: {}", path, e)))?;
+    let headers = rdr.headers().map_err(|e| ScrapyError::CsvParse(format!("{}: {}", path, e)))?;
+    let find = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+    let ex_col = find("ex_date").ok_or_else(|| ScrapyError::CsvParse(format!(
+        "missing required column 'ex_date' in header '{}'", headers.iter().collect::<Vec<_>>().join(","),
+    )))?;
+    let (split_col, div_col) = (find("split_ratio"), find("dividend"));
+
+    let mut actions = Vec::new();
+    for (i, record) in rdr.records().enumerate() {
+        let record_no = i + 1;
+        let record = record.map_err(|e| ScrapyError::CsvParse(format!("record {}: {}", record_no, e)))?;
+        let bad = |name: &str, raw: &str| ScrapyError::CsvParse(format!("record {}: bad {} value '{}'", record_no, name, raw));
+        let number = |col: Option<usize>, name: &str, default: f64| -> Result<f64> {
+            match col.and_then(|idx| record.get(idx)).filter(|raw| !raw.is_empty()) {
+                Some(raw) => raw.parse().map_err(|_| bad(name, raw)),
+                None => Ok(default),
+            }
+        };
+        let raw_date = record.get(ex_col).unwrap_or_default();
+        actions.push(CorporateAction {
+            ex_date: raw_date.parse().map_err(|_| bad("ex_date", raw_date))?,
+            split_ratio: number(split_col, "split_ratio", 1.0)?,
+            dividend: number(div_col, "dividend", 0.0)?,
+        });
+    }
+    Ok(actions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::America::New_York;
+
+    fn expected() -> DateTime<Utc> {
+        "2024-02-12T14:30:00Z".parse().unwrap()
+    }
+
+    #[test]
+    fn rfc3339_timestamps() {
+        assert_eq!(parse_timestamp("2024-02-12T14:30:00Z", chrono_tz::UTC).unwrap(), expected());
+        // An explicit offset wins over the assumed timezone
+        assert_eq!(parse_timestamp("2024-02-12T09:30:00-05:00", chrono_tz::UTC).unwrap(), expected());
+    }
+
+    #[test]
+    fn epoch_seconds() {
+        assert_eq!(parse_timestamp("1707748200", chrono_tz::UTC).unwrap(), expected());
+    }
+
+    #[test]
+    fn epoch_milliseconds() {
+        assert_eq!(parse_timestamp("1707748200000", chrono_tz::UTC).unwrap(), expected());
+    }
+
+    #[test]
+    fn naive_formats_in_utc() {
+        for raw in ["2024-02-12 14:30:00", "2024-02-12T14:30:00", "2024-02-12 14:30", "2024-02-12T14:30", "2024/02/12 14:30:00", "02/12/2024 14:30"] {
+            assert_eq!(parse_timestamp(raw, chrono_tz::UTC).unwrap(), expected(), "{}", raw);
+        }
+    }
+
+    #[test]
+    fn naive_formats_use_the_assumed_timezone() {
+        assert_eq!(parse_timestamp("2024-02-12 09:30:00", New_York).unwrap(), expected());
+        // 02:30 on 2024-03-10 was skipped by the spring-forward change
+        assert!(parse_timestamp("2024-03-10 02:30:00", New_York).is_err());
+    }
+
+    #[test]
+    fn unknown_formats_are_rejected() {
+        assert!(parse_timestamp("12 Feb 2024", chrono_tz::UTC).is_err());
+    }
+}
@@ -0,0 +1,45 @@
+use thiserror::Error;
+
+/// Failure modes of the fetcher and collectors, so library callers can match on them.
+#[derive(Debug, Error)]
+pub enum ScrapyError {
+    #[error("CSV parse error: {0}")]
+    CsvParse(String),
+
+    #[error("Network error: {0}")]
+    Network(#[from] reqwest::Error),
+
+    #[error("Request failed with status: {0}")]
+    Http(reqwest::StatusCode),
+
+    /// Error object returned by the provider itself, e.g. Yahoo's `chart.error`.
+    #[error("Yahoo API Error: {description} ({code})")]
+    Provider { code: String, description: String },
+
+    #[error("No data returned")]
+    NoData,
+
+    /// HTTP 429; `retry_after` is the `Retry-After` header in seconds, if sent.
+    #[error("Rate limited (429{})", .retry_after.map(|s| format!(", retry after {}s", s)).unwrap_or_default())]
+    RateLimited { retry_after: Option<u64> },
+
+    #[error("Gave up after {attempts} attempt(s): {last}")]
+    RetriesExhausted { attempts: u32, #[source] last: Box<ScrapyError> },
+
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+
+    #[error("Malformed response: {0}")]
+    Decode(String),
+
+    #[error("Failed to parse JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Failed to parse feed: {0}")]
+    Xml(#[from] quick_xml::Error),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, ScrapyError>;
@@ -1,9 +1,12 @@
-use anyhow::{Context, Result};
+use crate::error::{Result, ScrapyError};
 use chrono::{TimeZone, Utc};
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use crate::market::MinuteBar;
+use crate::net::{self, ClientOpts};
+use std::fs;
+use std::path::PathBuf;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[derive(Debug, Deserialize)]
 struct YahooResponse {
@@ -30,11 +33,18 @@ struct YahooResult {
 }
 
 #[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct YahooMeta {
     pub currency: Option<String>,
     pub symbol: String,
-    pub regularMarketPrice: Option<f64>,
-    pub chartPreviousClose: Option<f64>,
+    pub regular_market_price: Option<f64>,
+    pub chart_previous_close: Option<f64>,
+    /// Epoch seconds of the last regular-market trade behind `regular_market_price`
+    pub regular_market_time: Option<i64>,
+    /// Short exchange code, e.g. "NMS"
+    pub exchange_name: Option<String>,
+    /// Display name of the exchange, e.g. "NasdaqGS"
+    pub full_exchange_name: Option<String>,
     // These might not be in chart meta, but let's check. 
     // Usually chart meta has: currency, symbol, regularMarketPrice, gmtoffset.
     // Full quote is often not here, but basic price is.
@@ -43,6 +53,7 @@ pub struct YahooMeta {
 #[derive(Debug, Deserialize)]
 struct YahooIndicators {
     quote: Vec<YahooQuote>,
+    adjclose: Option<Vec<YahooAdjClose>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -54,55 +65,372 @@ struct YahooQuote {
     volume: Vec<Option<u64>>,
 }
 
+#[derive(Debug, Deserialize)]
+struct YahooAdjClose {
+    adjclose: Vec<Option<f64>>,
+}
+
+const YAHOO_INTERVALS: &[&str] = &["1m", "2m", "5m", "15m", "30m", "60m", "90m", "1h", "1d", "5d", "1wk", "1mo", "3mo"];
+const YAHOO_RANGES: &[&str] = &["1d", "5d", "1mo", "3mo", "6mo", "1y", "2y", "5y", "10y", "ytd", "max"];
+
+/// Checks `interval`/`range` against what the Yahoo chart API accepts, including the
+/// lookback limits on intraday intervals (1m: 7 days, <1h: 60 days, 1h: 730 days).
+pub fn validate_interval_range(interval: &str, range: &str) -> Result<()> {
+    if !YAHOO_INTERVALS.contains(&interval) {
+        return Err(ScrapyError::InvalidInput(format!("unsupported interval '{}' (allowed: {})", interval, YAHOO_INTERVALS.join(", "))));
+    }
+    if !YAHOO_RANGES.contains(&range) {
+        return Err(ScrapyError::InvalidInput(format!("unsupported range '{}' (allowed: {})", range, YAHOO_RANGES.join(", "))));
+    }
+    let max_days = match interval {
+        "1m" => 7,
+        "2m" | "5m" | "15m" | "30m" => 60,
+        "60m" | "90m" | "1h" => 730,
+        _ => return Ok(()),
+    };
+    if range_days(range).unwrap_or(i64::MAX) > max_days {
+        return Err(ScrapyError::InvalidInput(format!("Yahoo only serves {} bars for the last {} days; range '{}' is too long", interval, max_days, range)));
+    }
+    Ok(())
+}
+
+/// Upper bound in days of a Yahoo-style range string; None for unbounded ones (5y, max, ...).
+pub fn range_days(range: &str) -> Option<i64> {
+    match range {
+        "1d" => Some(1),
+        "5d" => Some(5),
+        "1mo" => Some(31),
+        "3mo" => Some(92),
+        "6mo" => Some(183),
+        "1y" | "ytd" => Some(366),
+        "2y" => Some(730),
+        _ => None,
+    }
+}
+
 // Return both bars AND metadata
-pub fn fetch_minute_bars(ticker: &str, days: i64) -> Result<(Vec<MinuteBar>, Option<YahooMeta>)> {
-    let range = "5d"; 
-    let urls = vec![
-        format!("https://query1.finance.yahoo.com/v8/finance/chart/{}?interval=1m&range={}", ticker, range),
-        format!("https://query2.finance.yahoo.com/v8/finance/chart/{}?interval=1m&range={}", ticker, range),
-    ];
+pub fn fetch_minute_bars(ticker: &str, _days: i64) -> Result<(Vec<MinuteBar>, Option<YahooMeta>)> {
+    fetch_bars(ticker, "1m", "5d")
+}
+
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// Chart hosts in failover order. An entry is a host name (https is assumed) or a base URL.
+pub const DEFAULT_YAHOO_HOSTS: &[&str] = &["query1.finance.yahoo.com", "query2.finance.yahoo.com"];
+
+pub fn fetch_bars(ticker: &str, interval: &str, range: &str) -> Result<(Vec<MinuteBar>, Option<YahooMeta>)> {
+    fetch_bars_with_retries(ticker, interval, range, DEFAULT_MAX_RETRIES)
+}
+
+pub fn fetch_bars_with_retries(ticker: &str, interval: &str, range: &str, max_retries: u32) -> Result<(Vec<MinuteBar>, Option<YahooMeta>)> {
+    fetch_bars_cached(ticker, interval, range, max_retries, None, DEFAULT_YAHOO_HOSTS)
+}
+
+/// Longest `Retry-After` worth sleeping through; anything longer would stall the CLI (or a
+/// `serve` request) far past any useful answer.
+pub const MAX_RETRY_AFTER_SECS: u64 = 60;
+
+pub const DEFAULT_CACHE_TTL_SECS: u64 = 900;
+
+/// Raw chart JSON kept on disk between runs, one file per `ticker|interval|range`.
+/// Each file starts with the unix time it was written, then the response body.
+#[derive(Debug, Clone)]
+pub struct ChartCache {
+    pub dir: PathBuf,
+    pub ttl: Duration,
+}
+
+impl ChartCache {
+    pub fn new(dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        Self { dir: dir.into(), ttl }
+    }
+
+    pub fn default_dir() -> PathBuf {
+        std::env::temp_dir().join("weekchart-cache")
+    }
+
+    fn key(ticker: &str, interval: &str, range: &str) -> String {
+        format!("{}|{}|{}", ticker, interval, range)
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", cache_file_stem(key)))
+    }
+
+    /// Cached body for `key` if it is younger than the TTL.
+    fn load(&self, key: &str) -> Option<String> {
+        let content = fs::read_to_string(self.path(key)).ok()?;
+        let (stamp, body) = content.split_once('\n')?;
+        let age = unix_now().checked_sub(stamp.trim().parse().ok()?)?;
+        (age < self.ttl.as_secs()).then(|| body.to_string())
+    }
 
-    let mut last_err = anyhow::anyhow!("No URLs tried");
+    /// Best effort: a cache that can't be written just means the next run fetches again.
+    fn store(&self, key: &str, body: &str) {
+        if fs::create_dir_all(&self.dir).is_ok() {
+            let _ = fs::write(self.path(key), format!("{}\n{}", unix_now(), body));
+        }
+    }
+}
+
+/// `key` with anything that isn't filename-safe percent-escaped, so BRK-B and BRK.B don't collide.
+pub(crate) fn cache_file_stem(key: &str) -> String {
+    let mut name = String::with_capacity(key.len());
+    for b in key.bytes() {
+        if b.is_ascii_alphanumeric() || b == b'-' || b == b'.' {
+            name.push(b as char);
+        } else {
+            name.push_str(&format!("%{:02X}", b));
+        }
+    }
+    name
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Fetches chart data from each of `hosts` in turn (`DEFAULT_YAHOO_HOSTS`: query1 then query2).
+/// Each host is retried up to `max_retries` times on 429/5xx and network errors with exponential
+/// backoff (500ms, 1s, 2s, ...) plus jitter, honoring `Retry-After` when Yahoo sends it; a
+/// `Retry-After` over `MAX_RETRY_AFTER_SECS` ends the fetch with `RateLimited` instead. Other 4xx
+/// responses are not retried. The crumb is requested from the first host.
+/// With a `cache`, a fresh enough cached body is used instead and successful responses are stored.
+pub fn fetch_bars_cached(ticker: &str, interval: &str, range: &str, max_retries: u32, cache: Option<&ChartCache>, hosts: &[&str]) -> Result<(Vec<MinuteBar>, Option<YahooMeta>)> {
+    if hosts.is_empty() {
+        return Err(ScrapyError::InvalidInput("no Yahoo hosts to fetch from".to_string()));
+    }
+    validate_interval_range(interval, range)?;
+    let cache_key = ChartCache::key(ticker, interval, range);
+    if let Some(body) = cache.and_then(|c| c.load(&cache_key)) {
+        // A corrupt entry falls through to a live fetch, which overwrites it
+        if let Ok(parsed) = parse_chart_body(&body) {
+            log::debug!("using cached chart for {} {} {}", ticker, interval, range);
+            return Ok(parsed);
+        }
+    }
+    let mut urls = chart_urls(hosts, ticker, interval, range);
+
+    // Cookie jar lives as long as the client, so the crumb's session cookie is reused for every attempt
+    let client = net::build_client(ClientOpts { cookie_store: true, ..ClientOpts::default() })?;
+
+    if let Some(crumb) = fetch_crumb(&client, &host_base(hosts[0])) {
+        append_crumb(&mut urls, &crumb)?;
+    }
+
+    let mut last_err = ScrapyError::NoData;
+    let mut attempts = 0;
 
     for (i, url) in urls.iter().enumerate() {
         if i > 0 {
             thread::sleep(Duration::from_secs(1));
         }
 
-        let client = reqwest::blocking::Client::builder()
-            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
-            .build()?;
+        for retry in 0..=max_retries {
+            if retry > 0 {
+                thread::sleep(last_delay(&last_err).unwrap_or_else(|| backoff_delay(retry - 1)));
+            }
+            attempts += 1;
 
-        let resp_res = client.get(url).send();
-        
-        match resp_res {
-            Ok(resp) => {
-                let status = resp.status();
-                if status.is_success() {
-                    let text = resp.text()?;
-                    let y_resp: YahooResponse = serde_json::from_str(&text).with_context(|| "Failed to parse Yahoo JSON")?;
-                    
-                    if let Some(res_list) = y_resp.chart.result {
-                        if !res_list.is_empty() {
-                            let bars = parse_yahoo_result(&res_list[0])?;
-                            let meta = res_list[0].meta.clone();
-                            return Ok((bars, Some(meta)));
+            net::acquire();
+            log::debug!("GET {} (attempt {})", url, attempts);
+            match client.get(url).send() {
+                Ok(resp) => {
+                    let status = resp.status();
+                    if status.is_success() {
+                        let text = resp.text()?;
+                        match parse_chart_body(&text) {
+                            Ok(parsed) => {
+                                if let Some(cache) = cache {
+                                    cache.store(&cache_key, &text);
+                                }
+                                return Ok(parsed);
+                            },
+                            Err(e) if is_definitive(&e) => return Err(e),
+                            Err(e @ (ScrapyError::Provider { .. } | ScrapyError::NoData)) => last_err = e,
+                            Err(e) => return Err(e),
+                        }
+                        break;
+                    } else if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                        let retry_after = resp.headers()
+                            .get(reqwest::header::RETRY_AFTER)
+                            .and_then(|v| v.to_str().ok())
+                            .and_then(|v| v.trim().parse::<u64>().ok());
+                        if retry_after.is_some_and(|secs| secs > MAX_RETRY_AFTER_SECS) {
+                            return Err(ScrapyError::RateLimited { retry_after });
+                        }
+                        last_err = ScrapyError::RateLimited { retry_after };
+                        log::debug!("rate limited by {}", url);
+                    } else if status.is_server_error() {
+                        log::debug!("{} from {}", status, url);
+                        last_err = ScrapyError::Http(status);
+                    } else {
+                        // 404 and friends won't change on retry
+                        last_err = ScrapyError::Http(status);
+                        // An unknown symbol is a 404 carrying a chart.error; the other host won't know it either
+                        if let Err(e) = resp.text().map_err(ScrapyError::from).and_then(|t| parse_chart_body(&t)) {
+                            if is_definitive(&e) {
+                                return Err(e);
+                            }
                         }
+                        break;
                     }
-                    if let Some(err) = y_resp.chart.error {
-                        last_err = anyhow::anyhow!("Yahoo API Error: {} ({})", err.description, err.code);
+                },
+                Err(e) => {
+                    log::debug!("request to {} failed: {}", url, e);
+                    last_err = ScrapyError::Network(e);
+                }
+            }
+        }
+    }
+
+    Err(ScrapyError::RetriesExhausted { attempts, last: Box::new(last_err) })
+}
+
+/// Async counterpart of `fetch_bars_cached` on `reqwest::Client`, for callers already running
+/// an executor. Tries each of `hosts` once (`DEFAULT_YAHOO_HOSTS` for query1 then query2);
+/// retry policy is left to the caller. The crumb is requested from the first host.
+pub async fn fetch_minute_bars_async(ticker: &str, interval: &str, range: &str, hosts: &[&str]) -> Result<(Vec<MinuteBar>, Option<YahooMeta>)> {
+    if hosts.is_empty() {
+        return Err(ScrapyError::InvalidInput("no Yahoo hosts to fetch from".to_string()));
+    }
+    validate_interval_range(interval, range)?;
+    let mut urls = chart_urls(hosts, ticker, interval, range);
+
+    let client = net::build_async_client(true)?;
+
+    if let Some(crumb) = fetch_crumb_async(&client, &host_base(hosts[0])).await {
+        append_crumb(&mut urls, &crumb)?;
+    }
+
+    let mut last_err = ScrapyError::NoData;
+    for url in &urls {
+        match client.get(url).send().await {
+            Ok(resp) if resp.status().is_success() => {
+                let text = resp.text().await?;
+                match parse_chart_body(&text) {
+                    Ok(parsed) => return Ok(parsed),
+                    Err(e) if is_definitive(&e) => return Err(e),
+                    Err(e @ (ScrapyError::Provider { .. } | ScrapyError::NoData)) => last_err = e,
+                    Err(e) => return Err(e),
+                }
+            },
+            Ok(resp) if resp.status() == reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                let retry_after = resp.headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.trim().parse::<u64>().ok());
+                last_err = ScrapyError::RateLimited { retry_after };
+            },
+            Ok(resp) => {
+                last_err = ScrapyError::Http(resp.status());
+                if resp.status().is_client_error() {
+                    if let Err(e) = resp.text().await.map_err(ScrapyError::from).and_then(|t| parse_chart_body(&t)) {
+                        if is_definitive(&e) {
+                            return Err(e);
+                        }
                     }
-                } else {
-                    last_err = anyhow::anyhow!("Request failed with status: {}", status);
                 }
             },
-            Err(e) => {
-                last_err = anyhow::anyhow!("Network error: {}", e);
-            }
+            Err(e) => last_err = ScrapyError::Network(e),
         }
     }
-    
-    Err(last_err)
+
+    Err(ScrapyError::RetriesExhausted { attempts: urls.len() as u32, last: Box::new(last_err) })
+}
+
+/// `https://{host}` for a bare host name; a base URL is used as given, minus any trailing `/`.
+fn host_base(host: &str) -> String {
+    let host = host.trim().trim_end_matches('/');
+    if host.contains("://") { host.to_string() } else { format!("https://{}", host) }
+}
+
+/// Chart endpoints in the order they should be tried.
+fn chart_urls(hosts: &[&str], ticker: &str, interval: &str, range: &str) -> Vec<String> {
+    hosts
+        .iter()
+        .map(|host| format!("{}/v8/finance/chart/{}?interval={}&range={}", host_base(host), ticker, interval, range))
+        .collect()
+}
+
+fn append_crumb(urls: &mut [String], crumb: &str) -> Result<()> {
+    for url in urls.iter_mut() {
+        let mut u = reqwest::Url::parse(url).map_err(|e| ScrapyError::InvalidInput(e.to_string()))?;
+        u.query_pairs_mut().append_pair("crumb", crumb);
+        *url = u.to_string();
+    }
+    Ok(())
+}
+
+/// Yahoo `chart.error` codes that no retry or other host will change, e.g. "Not Found" for an
+/// unknown symbol. These end the fetch at once instead of being retried.
+fn is_definitive(err: &ScrapyError) -> bool {
+    matches!(err, ScrapyError::Provider { code, .. } if ["Not Found", "Bad Request"].iter().any(|c| code.eq_ignore_ascii_case(c)))
+}
+
+/// Decodes a chart response body. `Provider` carries Yahoo's own `chart.error`;
+/// `NoData` means the response had neither a result nor an error.
+fn parse_chart_body(text: &str) -> Result<(Vec<MinuteBar>, Option<YahooMeta>)> {
+    let y_resp: YahooResponse = serde_json::from_str(text)?;
+    if let Some(res) = y_resp.chart.result.as_ref().and_then(|r| r.first()) {
+        return Ok((parse_yahoo_result(res)?, Some(res.meta.clone())));
+    }
+    match y_resp.chart.error {
+        Some(err) => Err(ScrapyError::Provider { code: err.code, description: err.description }),
+        None => Err(ScrapyError::NoData),
+    }
+}
+
+/// Seeds Yahoo session cookies via fc.yahoo.com and asks for a crumb.
+/// Returns None on any failure so callers fall back to unauthenticated requests.
+fn fetch_crumb(client: &reqwest::blocking::Client, base: &str) -> Option<String> {
+    // fc.yahoo.com usually answers 404 but still sets the A3 cookie we need
+    net::acquire();
+    let _ = client.get("https://fc.yahoo.com").send();
+    net::acquire();
+    let resp = client.get(format!("{}/v1/test/getcrumb", base)).send().ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    parse_crumb(&resp.text().ok()?)
+}
+
+/// `fetch_crumb` on the async client.
+async fn fetch_crumb_async(client: &reqwest::Client, base: &str) -> Option<String> {
+    let _ = client.get("https://fc.yahoo.com").send().await;
+    let resp = client.get(format!("{}/v1/test/getcrumb", base)).send().await.ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    parse_crumb(&resp.text().await.ok()?)
+}
+
+/// The crumb in a getcrumb response body, or None when the body is an HTML error page or
+/// otherwise doesn't look like one.
+fn parse_crumb(body: &str) -> Option<String> {
+    let crumb = body.trim();
+    if crumb.is_empty() || crumb.contains('<') || crumb.contains(' ') {
+        return None;
+    }
+    Some(crumb.to_string())
+}
+
+/// Server-requested delay carried by a previous 429, if any.
+fn last_delay(err: &ScrapyError) -> Option<Duration> {
+    match err {
+        ScrapyError::RateLimited { retry_after: Some(secs) } => Some(Duration::from_secs(*secs)),
+        _ => None,
+    }
+}
+
+/// 500ms * 2^attempt (capped at 8s) plus up to 250ms of jitter.
+fn backoff_delay(attempt: u32) -> Duration {
+    let base = 500u64.saturating_mul(1 << attempt.min(4));
+    let jitter = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64 % 250)
+        .unwrap_or(0);
+    Duration::from_millis(base + jitter)
 }
 
 fn parse_yahoo_result(data: &YahooResult) -> Result<Vec<MinuteBar>> {
@@ -115,18 +443,34 @@ fn parse_yahoo_result(data: &YahooResult) -> Result<Vec<MinuteBar>> {
         return Ok(vec![]); 
     }
     let quote = &data.indicators.quote[0];
+    // Only present for daily+ intervals; intraday responses omit it
+    let adjclose = data.indicators.adjclose.as_ref().and_then(|a| a.first()).map(|a| a.adjclose.as_slice());
+
+    bars_from_columns(timestamps, &quote.open, &quote.high, &quote.low, &quote.close, &quote.volume, adjclose)
+}
 
+/// Zips column-oriented OHLCV arrays (the layout most chart APIs return) into bars,
+/// dropping any row with a missing field. `adjclose`, when given, is aligned by index.
+fn bars_from_columns(
+    timestamps: &[i64],
+    open: &[Option<f64>],
+    high: &[Option<f64>],
+    low: &[Option<f64>],
+    close: &[Option<f64>],
+    volume: &[Option<u64>],
+    adjclose: Option<&[Option<f64>]>,
+) -> Result<Vec<MinuteBar>> {
     let mut bars = Vec::with_capacity(timestamps.len());
     
     for (i, &ts_secs) in timestamps.iter().enumerate() {
         if let (Some(o), Some(h), Some(l), Some(c), Some(v)) = (
-            quote.open.get(i).and_then(|x| *x),
-            quote.high.get(i).and_then(|x| *x),
-            quote.low.get(i).and_then(|x| *x),
-            quote.close.get(i).and_then(|x| *x),
-            quote.volume.get(i).and_then(|x| *x),
+            open.get(i).and_then(|x| *x),
+            high.get(i).and_then(|x| *x),
+            low.get(i).and_then(|x| *x),
+            close.get(i).and_then(|x| *x),
+            volume.get(i).and_then(|x| *x),
         ) {
-             let ts_utc = Utc.timestamp_opt(ts_secs, 0).single().ok_or_else(|| anyhow::anyhow!("Invalid timestamp"))?;
+             let ts_utc = Utc.timestamp_opt(ts_secs, 0).single().ok_or_else(|| ScrapyError::Decode(format!("invalid timestamp {}", ts_secs)))?;
             
             bars.push(MinuteBar {
                 ts_utc,
@@ -135,8 +479,46 @@ fn parse_yahoo_result(data: &YahooResult) -> Result<Vec<MinuteBar>> {
                 l,
                 c,
                 v,
+                adj_c: adjclose.and_then(|a| a.get(i).copied().flatten()),
             });
         }
     }
     Ok(bars)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOT_FOUND: &str = r#"{"chart":{"result":null,"error":{"code":"Not Found","description":"No data found, symbol may be delisted"}}}"#;
+    const BAD_REQUEST: &str = r#"{"chart":{"result":null,"error":{"code":"Bad Request","description":"Invalid input - interval=1m is not supported"}}}"#;
+    const INTERNAL: &str = r#"{"chart":{"result":null,"error":{"code":"Internal Server Error","description":"Timeout"}}}"#;
+
+    #[test]
+    fn not_found_is_definitive() {
+        let err = parse_chart_body(NOT_FOUND).unwrap_err();
+        assert!(matches!(&err, ScrapyError::Provider { description, .. } if description.contains("delisted")));
+        assert!(is_definitive(&err));
+    }
+
+    #[test]
+    fn bad_request_is_definitive() {
+        let err = parse_chart_body(BAD_REQUEST).unwrap_err();
+        assert!(matches!(&err, ScrapyError::Provider { code, .. } if code == "Bad Request"));
+        assert!(is_definitive(&err));
+    }
+
+    #[test]
+    fn other_provider_errors_are_retried() {
+        assert!(!is_definitive(&parse_chart_body(INTERNAL).unwrap_err()));
+        assert!(!is_definitive(&parse_chart_body(r#"{"chart":{"result":null,"error":null}}"#).unwrap_err()));
+    }
+
+    #[test]
+    fn crumb_bodies() {
+        assert_eq!(parse_crumb("  AbC.d/eF1\n").as_deref(), Some("AbC.d/eF1"));
+        assert_eq!(parse_crumb(""), None);
+        assert_eq!(parse_crumb("<html>Too Many Requests</html>"), None);
+        assert_eq!(parse_crumb("Too Many Requests"), None);
+    }
+}
@@ -0,0 +1,18 @@
+//! Builds the per-ticker data packet (price bars, news, insider, senate and finance
+//! sections) that the `weekchart` CLI prints. `build_packet` + `render_packet` are the
+//! entry points for embedding.
+
+#[cfg(feature = "parquet")]
+pub mod bars_parquet;
+pub mod collectors;
+pub mod csv_source;
+pub mod error;
+pub mod fetcher;
+pub mod market;
+pub mod net;
+pub mod packet;
+pub mod provider;
+pub mod robots;
+pub mod server;
+
+pub use packet::{append_checksum, build_packet, render_packet, render_packet_with_style, render_ranking, render_ranking_ndjson, BarOrder, CoverageReport, DelimiterStyle, InsiderActivity, PacketConfig, PacketFormat, PriceSource, RankEntry, Section, TickerPacket};
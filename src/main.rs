@@ -1,35 +1,636 @@
 use anyhow::{Context, Result};
-use clap::Parser;
+use chrono::{DateTime, FixedOffset};
+use clap::{Parser, Subcommand, ValueEnum};
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use std::io::{self, Write};
-use std::fs::File;
+use std::fs::{self, File, OpenOptions};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-mod market;
-mod collectors;
-mod fetcher; 
+use weekchart::collectors::{self, FallbackPolicy, NewsLocale, NewsSource};
+use weekchart::csv_source::{load_actions, load_csv_bars, load_csv_bars_presorted, ColumnMap, STDIN_PATH};
+use weekchart::fetcher::{self, ChartCache};
+use weekchart::market::{self, AssetClass, DupPolicy, HourBar, SessionKind, SessionSpec, TradingCalendar};
+use weekchart::net;
+use weekchart::server::{self, PacketRequest};
+use weekchart::provider::{PriceProvider, StooqProvider, YahooProvider};
+use weekchart::{append_checksum, build_packet, render_packet_with_style, render_ranking, render_ranking_ndjson, BarOrder, DelimiterStyle, PacketConfig, PacketFormat, PriceSource, RankEntry, Section, TickerPacket};
 
-use market::resample_1h_regular_session;
-use collectors::{NewsCollector, InsiderCollector, FinanceSnapshotCollector}; 
-use collectors::{GoogleNewsCollector, YahooInsiderCollector, YahooSnapshotCollector}; 
+#[derive(Clone, Copy, ValueEnum)]
+enum ProviderKind {
+    Yahoo,
+    Stooq,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum NewsSourceKind {
+    Google,
+    Yahoo,
+    Bing,
+    Merge,
+}
+
+impl From<NewsSourceKind> for NewsSource {
+    fn from(kind: NewsSourceKind) -> Self {
+        match kind {
+            NewsSourceKind::Google => NewsSource::Google,
+            NewsSourceKind::Yahoo => NewsSource::Yahoo,
+            NewsSourceKind::Bing => NewsSource::Bing,
+            NewsSourceKind::Merge => NewsSource::Merge,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OrderArg {
+    Asc,
+    Desc,
+}
+
+impl From<OrderArg> for BarOrder {
+    fn from(order: OrderArg) -> Self {
+        match order {
+            OrderArg::Asc => BarOrder::Asc,
+            OrderArg::Desc => BarOrder::Desc,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DelimiterKind {
+    Angle,
+    JsonSafe,
+}
+
+impl From<DelimiterKind> for DelimiterStyle {
+    fn from(kind: DelimiterKind) -> Self {
+        match kind {
+            DelimiterKind::Angle => DelimiterStyle::Angle,
+            DelimiterKind::JsonSafe => DelimiterStyle::JsonSafe,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum FallbackKind {
+    RssDescription,
+    Skip,
+    MarkUnavailable,
+}
+
+impl From<FallbackKind> for FallbackPolicy {
+    fn from(kind: FallbackKind) -> Self {
+        match kind {
+            FallbackKind::RssDescription => FallbackPolicy::RssDescription,
+            FallbackKind::Skip => FallbackPolicy::Skip,
+            FallbackKind::MarkUnavailable => FallbackPolicy::MarkUnavailable,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DupKind {
+    First,
+    Last,
+    Error,
+}
+
+impl From<DupKind> for DupPolicy {
+    fn from(kind: DupKind) -> Self {
+        match kind {
+            DupKind::First => DupPolicy::First,
+            DupKind::Last => DupPolicy::Last,
+            DupKind::Error => DupPolicy::Error,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SessionArg {
+    Regular,
+    PreMarket,
+    AfterHours,
+    Extended,
+}
+
+impl From<SessionArg> for SessionKind {
+    fn from(arg: SessionArg) -> Self {
+        match arg {
+            SessionArg::Regular => SessionKind::Regular,
+            SessionArg::PreMarket => SessionKind::PreMarket,
+            SessionArg::AfterHours => SessionKind::AfterHours,
+            SessionArg::Extended => SessionKind::Extended,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum AssetClassArg {
+    Equity,
+    Crypto,
+    Futures,
+}
+
+impl From<AssetClassArg> for AssetClass {
+    fn from(arg: AssetClassArg) -> Self {
+        match arg {
+            AssetClassArg::Equity => AssetClass::Equity,
+            AssetClassArg::Crypto => AssetClass::Crypto,
+            AssetClassArg::Futures => AssetClass::Futures,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum RankBy {
+    Change,
+    Volume,
+}
+
+impl RankBy {
+    fn label(self) -> &'static str {
+        match self {
+            RankBy::Change => "change",
+            RankBy::Volume => "volume",
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+    Ndjson,
+    Parquet,
+}
+
+/// `--col-*` overrides for CSV sources: a header name, or a 0-based column index.
+#[derive(clap::Args)]
+struct ColumnArgs {
+    /// CSV column holding the timestamp (Default: header `ts`)
+    #[arg(long)]
+    col_ts: Option<String>,
+
+    /// CSV column holding the open (Default: header `o`)
+    #[arg(long)]
+    col_open: Option<String>,
 
+    /// CSV column holding the high (Default: header `h`)
+    #[arg(long)]
+    col_high: Option<String>,
+
+    /// CSV column holding the low (Default: header `l`)
+    #[arg(long)]
+    col_low: Option<String>,
+
+    /// CSV column holding the close (Default: header `c`)
+    #[arg(long)]
+    col_close: Option<String>,
+
+    /// CSV column holding the volume (Default: header `v`)
+    #[arg(long)]
+    col_vol: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run as a long-lived HTTP service answering `GET /packet?ticker=AAPL&window_days=7&format=json`;
+    /// the other flags apply to every packet it builds
+    Serve {
+        #[arg(long, default_value = "8080")]
+        port: u16,
+
+        /// Address to listen on; use 0.0.0.0 to accept connections from other hosts
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: String,
+
+        /// Answer repeat requests for the same ticker, window and format from memory for this
+        /// many seconds
+        #[arg(long, default_value = "60")]
+        packet_ttl: u64,
+    },
+}
+
+/// Packs a symbol's recent price bars, news, insider, senate and finance data into one
+/// text (or JSON) packet for LLM prompts
 #[derive(Parser)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Symbol, or comma-separated symbols (AAPL,MSFT,NVDA)
     #[arg(long)]
     ticker: Option<String>,
 
+    /// File with one symbol per line
+    #[arg(long)]
+    tickers_file: Option<String>,
+
+    /// Directory interactive mode searches for `{TICKER}.csv` before the defaults; repeatable.
+    /// Replaces the colon-separated SCRAPY_DATA_DIRS variable
+    #[arg(long = "data-dir")]
+    data_dirs: Vec<String>,
+
+    /// Read minute bars from this CSV instead of Yahoo; `{ticker}` is replaced per symbol,
+    /// and `-` reads standard input (one --ticker only)
+    #[arg(long)]
+    source_path: Option<String>,
+
+    /// Timezone for CSV timestamps without an offset (e.g. `2024-02-12 09:30:00`)
+    #[arg(long, default_value = "UTC", value_parser = parse_tz)]
+    assume_tz: chrono_tz::Tz,
+
+    /// Read --source-path row by row, holding one day at a time (the CSV must be time-sorted;
+    /// not combinable with --adjust or --drop-outliers)
+    #[arg(long, requires = "source_path", conflicts_with = "save_csv")]
+    stream: bool,
+
+    /// Trust the CSV to be in time order: skip sorting it and fail on the first record that
+    /// isn't (without it, out-of-order rows are sorted and counted under --verbose)
+    #[arg(long)]
+    assume_sorted: bool,
+
+    /// Check every minute bar for l <= o, c <= h and report violations in NOTES
+    #[arg(long)]
+    validate_ohlc: bool,
+
+    /// Like --validate-ohlc, then swap inverted high/low and clamp open/close into [low, high]
+    #[arg(long)]
+    repair_ohlc: bool,
+
+    /// Emit the packet with a NOTES warning when the CSV has no data rows, instead of failing
+    #[arg(long)]
+    allow_empty: bool,
+
+    /// Also write the minute bars, fetched or loaded, to this `ts,o,h,l,c,v` CSV so later
+    /// runs can use it as --source-path; `{ticker}` expands per symbol
+    #[arg(long)]
+    save_csv: Option<String>,
+
+    #[command(flatten)]
+    columns: ColumnArgs,
+
+    /// Back-adjust prices and volume for the splits/dividends in --actions-file
+    #[arg(long)]
+    adjust: bool,
+
+    /// CSV of corporate actions (ex_date,split_ratio,dividend); `{ticker}` is replaced per symbol
+    #[arg(long, requires = "adjust")]
+    actions_file: Option<String>,
+
+    /// Which minute bar to keep when several share a timestamp; `error` fails on conflicting copies
+    #[arg(long, value_enum, default_value = "last")]
+    duplicates: DupKind,
+
     #[arg(long, default_value = "7")]
     window_days: i64,
 
+    /// First trading day (YYYY-MM-DD, session-local) to keep; with --to, overrides --window-days
+    #[arg(long, requires = "to")]
+    from: Option<chrono::NaiveDate>,
+
+    /// Last trading day (YYYY-MM-DD, session-local) to keep, inclusive
+    #[arg(long, requires = "from")]
+    to: Option<chrono::NaiveDate>,
+
+    /// Where to fetch bars from when no --source-path is given
+    #[arg(long, value_enum, default_value = "yahoo")]
+    provider: ProviderKind,
+
+    /// Bar interval to fetch (1m, 2m, 5m, ...)
+    #[arg(long, default_value = "1m")]
+    interval: String,
+
+    /// Lookback range to fetch (5d, 1mo, ...)
+    #[arg(long, default_value = "5d")]
+    range: String,
+
+    /// Global cap on outbound HTTP requests per second (0 disables)
+    #[arg(long, default_value_t = net::DEFAULT_RPS)]
+    rps: f64,
+
+    /// Send this user agent on every request instead of rotating through the built-in pool
+    #[arg(long)]
+    user_agent: Option<String>,
+
+    /// Send every request through this proxy (http:// or https://); defaults to
+    /// HTTPS_PROXY/HTTP_PROXY, and NO_PROXY hosts always go direct
+    #[arg(long)]
+    proxy: Option<String>,
+
+    /// Extra trusted root certificate (PEM or DER), e.g. a corporate proxy's CA
+    #[arg(long)]
+    ca_cert: Option<String>,
+
+    /// Retries per Yahoo host on 429/5xx/network errors
+    #[arg(long, default_value_t = fetcher::DEFAULT_MAX_RETRIES)]
+    max_retries: u32,
+
+    /// Yahoo chart host (or base URL) to try, in order; repeatable. Replaces query1/query2
+    #[arg(long = "yahoo-host")]
+    yahoo_hosts: Vec<String>,
+
+    /// Reuse cached Yahoo chart responses younger than this many seconds
+    #[arg(long, default_value_t = fetcher::DEFAULT_CACHE_TTL_SECS)]
+    cache_ttl: u64,
+
+    /// Always fetch live chart data and news feeds, bypassing the on-disk cache
+    #[arg(long)]
+    no_cache: bool,
+
+    /// When the live price fetch fails, load the first existing `{TICKER}.csv` candidate
+    /// (`--data-dir`, `data/`, working directory) instead and say so under NOTES
+    #[arg(long, conflicts_with = "source_path")]
+    fallback_csv: bool,
+
+    /// Bar size for the price section: 5m, 15m, 30m, 1h, ..., or 1d for one bar per session
+    #[arg(long, default_value = "1h", value_parser = parse_bar_size)]
+    bar_size: u32,
+
+    /// Session timezone (IANA name), e.g. Europe/London
+    #[arg(long, default_value = "America/New_York", value_parser = parse_tz)]
+    session_tz: chrono_tz::Tz,
+
+    /// Which session to chart: regular 09:30-16:00, pre-market 04:00-09:30, after-hours
+    /// 16:00-20:00 or extended 04:00-20:00
+    #[arg(long, value_enum, default_value = "regular")]
+    session: SessionArg,
+
+    /// Instrument type: equity sessions, crypto (every minute, 24/7, UTC buckets from 00:00)
+    /// or futures (a near-24h session, 00:00-23:00 unless --session-open/--session-close say otherwise)
+    #[arg(long, value_enum, default_value = "equity")]
+    asset_class: AssetClassArg,
+
+    /// Session open in local time (HH:MM, inclusive); defaults to the --session open
+    #[arg(long, value_parser = parse_hhmm)]
+    session_open: Option<chrono::NaiveTime>,
+
+    /// Session close in local time (HH:MM, exclusive); defaults to the --session close
+    #[arg(long, value_parser = parse_hhmm)]
+    session_close: Option<chrono::NaiveTime>,
+
+    /// Exchange holiday (YYYY-MM-DD) excluded from the window; repeatable
+    #[arg(long = "holiday")]
+    holidays: Vec<chrono::NaiveDate>,
+
+    /// Emit a DATA_GAPS section listing holes in the minute data
+    #[arg(long)]
+    report_gaps: bool,
+
+    /// Minimum spacing between consecutive minute bars reported as a gap
+    #[arg(long, default_value = "120")]
+    max_gap_secs: i64,
+
+    /// Emit a COVERAGE section listing days with fewer than this percentage of the
+    /// session's expected bars (e.g. 80)
+    #[arg(long, value_parser = clap::builder::RangedU64ValueParser::<u32>::new().range(1..=100))]
+    min_coverage: Option<u32>,
+
+    /// Forward-fill missing in-session buckets with synthetic zero-volume bars
+    #[arg(long)]
+    fill_gaps: bool,
+
+    /// Add a `partial` column flagging each day's last bar when it has fewer prints than the
+    /// input interval fits into it, e.g. a bar still in progress
+    #[arg(long)]
+    mark_partial: bool,
+
+    /// Add a `notional` column: traded value per bar, the sum of close * volume over its minutes
+    #[arg(long)]
+    notional: bool,
+
+    /// Shorter price section: a `DATE:` line per day, then bars keyed by local HH:MM only
+    #[arg(long)]
+    compact_bars: bool,
+
+    /// Append simple and log returns between consecutive bars of the same session
+    #[arg(long)]
+    returns: bool,
+
+    /// Append an `sma_N` column: mean close of the last N bars, rolling across days
+    #[arg(long, value_parser = parse_period)]
+    sma: Option<usize>,
+
+    /// Append an `ema_N` column: N-bar exponential moving average of closes, rolling across days
+    #[arg(long, value_parser = parse_period)]
+    ema: Option<usize>,
+
+    /// Decimals for prices (OHLC, VWAP, moving averages, stats), 0-8; 2 suits most equities
+    #[arg(long, default_value_t = weekchart::packet::DEFAULT_PRICE_PRECISION, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(0..=8))]
+    price_precision: usize,
+
+    /// Bar order in the output: oldest first (asc) or newest first (desc)
+    #[arg(long, value_enum, default_value = "asc")]
+    order: OrderArg,
+
+    /// Also emit one bar per day, folded from the bars above (PRICE_BARS_1D_CSV)
+    #[arg(long)]
+    include_daily: bool,
+
+    /// Drop minute bars whose close is far from the rolling median before resampling
+    #[arg(long)]
+    drop_outliers: bool,
+
+    /// Deviation, in robust standard deviations, beyond which --drop-outliers removes a bar
+    #[arg(long, default_value = "10.0")]
+    outlier_z: f64,
+
     #[arg(long)]
     no_news: bool,
 
+    /// RSS feed to read headlines from; `merge` queries all of them and dedups
+    #[arg(long, value_enum, default_value = "google")]
+    news_source: NewsSourceKind,
+
+    /// Number of news articles to scrape (max 25)
+    #[arg(long, default_value = "10")]
+    news_count: usize,
+
+    /// Article pages fetched in parallel
+    #[arg(long, default_value = "4")]
+    news_concurrency: usize,
+
+    /// Stop starting network requests for the news/insider/senate/finance sections this many
+    /// seconds into the run; what was collected is kept and NOTES says what was cut
+    #[arg(long)]
+    deadline: Option<u64>,
+
+    /// `--deadline` as an instant, set once at startup so a batch shares it
+    #[arg(skip)]
+    run_deadline: Option<Instant>,
+
+    /// Headlines, dates and sources only: don't fetch article pages, use the feed's description
     #[arg(long)]
-    no_senate: bool, 
+    news_headlines_only: bool,
+
+    /// CSS selector for article paragraphs (e.g. "article p", ".article-body p"), tried before
+    /// the built-in content detection; repeatable, first match wins
+    #[arg(long = "content-selector", value_parser = parse_css)]
+    content_selectors: Vec<String>,
+
+    /// Scrape article pages even where the publisher's robots.txt disallows it
+    #[arg(long)]
+    ignore_robots: bool,
+
+    /// Scraped paragraphs shorter than this (bytes) are dropped as boilerplate
+    #[arg(long, default_value_t = collectors::DEFAULT_MIN_PARAGRAPH_LEN)]
+    min_paragraph_len: usize,
+
+    /// A scraped article body shorter than this (bytes) counts as a failed scrape
+    #[arg(long, default_value_t = collectors::DEFAULT_MIN_SNIPPET_LEN)]
+    min_snippet_len: usize,
+
+    /// What a news item shows when its article couldn't be scraped: the feed's description,
+    /// nothing (item dropped), or "Content unavailable."
+    #[arg(long, value_enum, default_value = "rss-description")]
+    news_fallback: FallbackKind,
+
+    /// Language of the Google News / Yahoo Finance feeds (Google's `hl`), e.g. en-GB
+    #[arg(long, default_value = "en-US")]
+    news_lang: String,
+
+    /// Country of the Google News / Yahoo Finance feeds (Google's `gl`), e.g. GB
+    #[arg(long, default_value = "US")]
+    news_country: String,
+
+    /// Truncate each news snippet at a sentence boundary near this many characters
+    #[arg(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    snippet_max_chars: Option<usize>,
+
+    /// Keep at most this many sentences of each news snippet
+    #[arg(long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    snippet_max_sentences: Option<usize>,
+
+    /// Skip insider transactions and institutional holders
+    #[arg(long)]
+    no_insider: bool,
+
+    /// Skip Senate trading disclosures
+    #[arg(long)]
+    no_senate: bool,
 
     #[arg(long)]
     no_finance: bool,
     
-    #[arg(long)]
+    /// Write the packet here instead of stdout; `{ticker}` expands per symbol
+    #[arg(long, alias = "out")]
     output: Option<String>,
+
+    /// Overwrite an existing output file
+    #[arg(long)]
+    force: bool,
+
+    /// Also write a JSON manifest: per symbol its bar coverage, news/insider/senate/finance
+    /// status and output file, plus the symbols that failed
+    #[arg(long)]
+    manifest: Option<String>,
+
+    /// Gzip the output file (implied when the path ends in .gz); stdout is never compressed
+    #[arg(long)]
+    gzip: bool,
+
+    /// Output format: delimited text (default), a JSON object per ticker, one JSON line per
+    /// bar (ndjson), or Parquet bars (needs --output and a build with `--features parquet`)
+    #[arg(long, value_enum, default_value = "text")]
+    format: OutputFormat,
+
+    /// Section markers in the text packet: `<<<NAME>>>` (angle) or `[[NAME]]` (json-safe,
+    /// emitted as TICKER_PACKET_V2)
+    #[arg(long, value_enum, default_value = "angle")]
+    delimiter_style: DelimiterKind,
+
+    /// End each text packet with a CHECKSUM section (CRC-32 and line count of the packet body)
+    #[arg(long)]
+    checksum: bool,
+
+    /// Export only bars newer than the last run's (per symbol, kept in --state-dir) as ndjson
+    /// bar records, appended to --output; the first run exports everything
+    #[arg(long, requires = "state_dir")]
+    incremental: bool,
+
+    /// Directory holding the per-symbol last-exported timestamp for --incremental
+    #[arg(long, requires = "incremental")]
+    state_dir: Option<String>,
+
+    /// Validate flags and load each CSV source, print a short report and exit without
+    /// producing a packet or touching the network
+    #[arg(long)]
+    check: bool,
+
+    /// Rank the symbols by --rank-by and emit full packets only for the top N (with `change`,
+    /// also the bottom N), after a RANKING block listing every symbol
+    #[arg(long)]
+    top: Option<usize>,
+
+    /// Metric for --top: percent change over the window, or total volume
+    #[arg(long, value_enum, default_value = "change", requires = "top")]
+    rank_by: RankBy,
+
+    /// Only print warnings and errors on stderr
+    #[arg(long, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Also log URLs fetched, retries and dropped items on stderr; `RUST_LOG` overrides both
+    #[arg(long)]
+    verbose: bool,
+}
+
+/// Diagnostics go to stderr so stdout carries nothing but the packet.
+fn init_logging(args: &Args) {
+    let level = if args.quiet {
+        log::LevelFilter::Warn
+    } else if args.verbose {
+        log::LevelFilter::Debug
+    } else {
+        log::LevelFilter::Info
+    };
+    // Dependencies stay at warn so --verbose shows our requests, not hyper's
+    env_logger::Builder::new()
+        .filter_level(log::LevelFilter::Warn)
+        .filter_module("weekchart", level)
+        .format_timestamp(None)
+        .format_target(false)
+        .parse_default_env()
+        .init();
+}
+
+fn parse_period(s: &str) -> std::result::Result<usize, String> {
+    match s.trim().parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("'{}' is not a positive number of bars", s)),
+    }
+}
+
+fn parse_bar_size(s: &str) -> std::result::Result<u32, String> {
+    let s = s.trim().to_lowercase();
+    if s == "1d" {
+        return Ok(market::DAILY_BAR_MINUTES);
+    }
+    let (num, mult) = if let Some(n) = s.strip_suffix('h') {
+        (n, 60)
+    } else if let Some(n) = s.strip_suffix('m') {
+        (n, 1)
+    } else {
+        (s.as_str(), 1)
+    };
+    match num.parse::<u32>() {
+        Ok(n) if n > 0 && n.checked_mul(mult).is_some_and(|m| m <= 24 * 60) => Ok(n * mult),
+        _ => Err(format!("invalid bar size '{}' (expected e.g. 5m, 15m, 1h, 1d)", s)),
+    }
+}
+
+fn parse_tz(s: &str) -> std::result::Result<chrono_tz::Tz, String> {
+    s.parse::<chrono_tz::Tz>().map_err(|_| format!("unknown timezone '{}'", s))
+}
+
+fn parse_hhmm(s: &str) -> std::result::Result<chrono::NaiveTime, String> {
+    chrono::NaiveTime::parse_from_str(s, "%H:%M").map_err(|_| format!("invalid time '{}' (expected HH:MM)", s))
+}
+
+fn parse_css(s: &str) -> std::result::Result<String, String> {
+    collectors::parse_selector(s).map(|_| s.to_string()).map_err(|e| e.to_string())
 }
 
 fn prompt_input(prompt: &str) -> Result<String> {
@@ -40,159 +641,717 @@ fn prompt_input(prompt: &str) -> Result<String> {
     Ok(buffer.trim().to_string())
 }
 
-fn main() -> Result<()> {
-    let args_cli = Args::parse();
-    let is_interactive = args_cli.ticker.is_none();
-    
-    // Interactive Mode Logic
-    let ticker = match args_cli.ticker {
-        Some(t) => t.to_uppercase(),
-        None => {
-            let t = prompt_input("Enter Ticker (e.g. AMZN): ")?;
-            if t.is_empty() {
-                anyhow::bail!("Ticker cannot be empty");
-            }
-            t.to_uppercase()
-        }
+/// Where interactive mode looks for a symbol's minute bars; fetched bars are saved to the first.
+/// `--data-dir` (else `SCRAPY_DATA_DIRS`) directories come ahead of `data/` and the working
+/// directory.
+fn local_csv_candidates(ticker: &str, data_dirs: &[String]) -> Vec<String> {
+    let env_dirs: Vec<String> = match std::env::var("SCRAPY_DATA_DIRS") {
+        Ok(dirs) if data_dirs.is_empty() => dirs.split(':').filter(|d| !d.is_empty()).map(str::to_string).collect(),
+        _ => Vec::new(),
     };
+    let mut candidates: Vec<String> = data_dirs.iter().chain(&env_dirs)
+        .map(|dir| Path::new(dir).join(format!("{}.csv", ticker)).to_string_lossy().into_owned())
+        .collect();
+    candidates.extend([format!("data/{}.csv", ticker), format!("{}.csv", ticker)]);
+    candidates
+}
 
-    if is_interactive {
-        eprintln!("Fetching data for {} from the internet...", ticker);
-        eprintln!("(This may take a few seconds to scrape news bodies and insider info)");
+/// Symbols from `--ticker` (comma-separated) and `--tickers-file` (one per line, `#` comments).
+fn collect_tickers(args: &Args) -> Result<Vec<String>> {
+    let mut raw = Vec::new();
+    if let Some(list) = &args.ticker {
+        raw.extend(list.split(',').map(str::to_string));
+    }
+    if let Some(path) = &args.tickers_file {
+        let text = std::fs::read_to_string(path).with_context(|| format!("failed to read tickers file {}", path))?;
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("");
+            raw.extend(line.split(',').map(str::to_string));
+        }
     }
 
-    let (rows, meta) = fetcher::fetch_minute_bars(&ticker, args_cli.window_days)
-        .with_context(|| format!("Failed to fetch price data for {}", ticker))?;
-    
-    let chart = resample_1h_regular_session(&ticker, &rows, args_cli.window_days);
-
-    // 3. Collect Extra Data (Live!)
-    let news_block = if !args_cli.no_news {
-        let col = GoogleNewsCollector;
-        match col.collect_news(&ticker, args_cli.window_days) {
-            Ok(items) => {
-                if items.is_empty() {
-                    "No recent news found.".to_string()
-                } else {
-                     items.iter().take(10).map(|item| {
-                         format!("{} | {} | {}\n{}\n-------------------", 
-                            item.datetime, item.source, item.headline, item.content_snippet)
-                     }).collect::<Vec<_>>().join("\n")
-                }
-            }
-            Err(e) => format!("Error fetching news: {}", e)
+    let mut tickers: Vec<String> = Vec::new();
+    for t in raw {
+        let t = t.trim().to_uppercase();
+        if !t.is_empty() && !tickers.contains(&t) {
+            tickers.push(t);
         }
-    } else {
-        String::new()
+    }
+    if tickers.is_empty() {
+        anyhow::bail!("No tickers given");
+    }
+    Ok(tickers)
+}
+
+impl From<&ColumnArgs> for ColumnMap {
+    fn from(a: &ColumnArgs) -> Self {
+        ColumnMap {
+            ts: a.col_ts.clone(),
+            open: a.col_open.clone(),
+            high: a.col_high.clone(),
+            low: a.col_low.clone(),
+            close: a.col_close.clone(),
+            vol: a.col_vol.clone(),
+        }
+    }
+}
+
+/// Maps the CLI flags onto a `PacketConfig` for one symbol.
+fn packet_config(ticker: &str, args: &Args, session: &SessionSpec) -> PacketConfig {
+    let source = match &args.source_path {
+        Some(path) => PriceSource::Csv {
+            path: path.replace("{ticker}", ticker),
+            assume_tz: args.assume_tz,
+            columns: ColumnMap::from(&args.columns),
+        },
+        None => {
+            let provider: Box<dyn PriceProvider> = match args.provider {
+                ProviderKind::Yahoo => Box::new(YahooProvider {
+                    max_retries: args.max_retries,
+                    cache: (!args.no_cache).then(|| ChartCache::new(ChartCache::default_dir(), Duration::from_secs(args.cache_ttl))),
+                    hosts: args.yahoo_hosts.clone(),
+                }),
+                ProviderKind::Stooq => Box::new(StooqProvider),
+            };
+            PriceSource::Provider { provider, interval: args.interval.clone(), range: args.range.clone() }
+        },
     };
 
-    let insider_block = if !args_cli.no_senate { 
-        let col = YahooInsiderCollector;
-        // Pass the window_days for strict filtering!
-        match col.collect_activity(&ticker, args_cli.window_days) {
-            Ok((trades, holders)) => {
-                let mut s = String::new();
-                if trades.is_empty() {
-                    s.push_str(&format!("--- RECENT INSIDER TRANSACTIONS (Last {} Days) ---\n", args_cli.window_days));
-                    s.push_str("No transactions found in this period.\n");
-                } else {
-                    s.push_str(&format!("--- RECENT INSIDER TRANSACTIONS (Last {} Days) ---\n", args_cli.window_days));
-                    s.push_str("# Date | Entity | Relation | Type | Value\n");
-                    for t in trades {
-                        s.push_str(&format!("{} | {} | {} | {} | {}\n", t.date, t.entity_name, t.relation, t.transaction_type, t.value_approx));
-                    }
-                }
-                
-                s.push_str("\n--- TOP INSTITUTIONAL & FUND HOLDERS ---\n");
-                s.push_str("# Holder | % Held\n");
-                for h in holders {
-                     s.push_str(&format!("{} | {}\n", h.holder_name, h.pct_held));
+    PacketConfig {
+        window_days: args.window_days,
+        bar_minutes: args.bar_size,
+        session: session.clone(),
+        actions_file: args.actions_file.as_ref().filter(|_| args.adjust).map(|p| p.replace("{ticker}", ticker)),
+        dup_policy: args.duplicates.into(),
+        date_range: args.from.zip(args.to),
+        stream: args.stream,
+        assume_sorted: args.assume_sorted,
+        allow_empty: args.allow_empty,
+        validate_ohlc: args.validate_ohlc,
+        repair_ohlc: args.repair_ohlc,
+        save_csv: args.save_csv.as_ref().map(|p| p.replace("{ticker}", ticker)),
+        outlier_z: args.drop_outliers.then_some(args.outlier_z),
+        fill_gaps: args.fill_gaps,
+        returns: args.returns,
+        sma: args.sma,
+        ema: args.ema,
+        price_precision: args.price_precision,
+        bar_order: args.order.into(),
+        mark_partial: args.mark_partial,
+        notional: args.notional,
+        compact_bars: args.compact_bars,
+        include_daily: args.include_daily,
+        max_gap_secs: args.report_gaps.then_some(args.max_gap_secs),
+        min_coverage_pct: args.min_coverage,
+        news: !args.no_news,
+        news_source: args.news_source.into(),
+        news_count: args.news_count,
+        news_concurrency: args.news_concurrency,
+        respect_robots: !args.ignore_robots,
+        news_min_paragraph_len: args.min_paragraph_len,
+        news_min_snippet_len: args.min_snippet_len,
+        news_fallback: args.news_fallback.into(),
+        news_snippet_max_chars: args.snippet_max_chars,
+        news_snippet_max_sentences: args.snippet_max_sentences,
+        news_locale: NewsLocale::new(&args.news_lang, &args.news_country),
+        news_headlines_only: args.news_headlines_only,
+        news_content_selectors: args.content_selectors.clone(),
+        news_feed_cache: (!args.no_cache).then(|| ChartCache::default_dir().join("feeds")),
+        insider: !args.no_insider,
+        senate: !args.no_senate,
+        finance: !args.no_finance,
+        deadline: args.run_deadline,
+        ..PacketConfig::new(ticker, source)
+    }
+}
+
+/// `--check` report for one symbol: parses the CSV source (and actions file) and reports
+/// row counts, session coverage and the timestamp range. Provider sources only get their
+/// flags validated, since checking them would mean fetching.
+fn check_inputs(ticker: &str, args: &Args, session: &SessionSpec) -> Result<()> {
+    let config = packet_config(ticker, args, session);
+    println!("CHECK {}", ticker);
+    match &config.source {
+        PriceSource::Csv { path, assume_tz, columns } => {
+            let load = if config.assume_sorted { load_csv_bars_presorted } else { load_csv_bars };
+            let rows = load(path, *assume_tz, columns).with_context(|| format!("Failed to load price data from {}", path))?;
+            let cov = market::session_coverage(&rows, session);
+            let ts = |t: Option<chrono::DateTime<chrono::Utc>>| t.map(|t| t.to_rfc3339()).unwrap_or_else(|| "N/A".to_string());
+            println!("  source: {}", path);
+            println!("  rows: {} ({} in session {}, {} outside)", cov.rows, cov.in_session, session.hours_label(), cov.rows - cov.in_session);
+            println!("  trading days: {} (window keeps {})", cov.trading_days, cov.trading_days.min(args.window_days.max(0) as usize));
+            println!("  first: {}", ts(cov.first));
+            println!("  last: {}", ts(cov.last));
+            if cov.in_session == 0 {
+                anyhow::bail!("{}: no rows fall inside the {} {} session", path, session.tz.name(), session.hours_label());
+            }
+        },
+        PriceSource::Provider { interval, range, .. } => {
+            if matches!(args.provider, ProviderKind::Yahoo) {
+                fetcher::validate_interval_range(interval, range)?;
+            }
+            println!("  source: provider, interval {} over {} (not fetched)", interval, range);
+        },
+    }
+    if let Some(path) = &config.actions_file {
+        let actions = load_actions(path).with_context(|| format!("Failed to load corporate actions from {}", path))?;
+        println!("  actions: {} from {}", actions.len(), path);
+    }
+    Ok(())
+}
+
+/// `--top` ranking carried alongside the packets: the metric name and every ranked symbol.
+type Ranking<'a> = Option<(&'a str, &'a [RankEntry])>;
+
+fn render(packets: &[TickerPacket], format: OutputFormat, args: &Args, ranking: Ranking) -> Result<String> {
+    let style = DelimiterStyle::from(args.delimiter_style);
+    Ok(match format {
+        // Parquet only carries the bars; the full text packet goes to its sidecar
+        OutputFormat::Text | OutputFormat::Parquet => {
+            let mut out = ranking.map(|(by, entries)| render_ranking(entries, by, style)).unwrap_or_default();
+            out.extend(packets.iter().map(|p| {
+                let mut text = render_packet_with_style(p, PacketFormat::Text, style);
+                if args.checksum {
+                    append_checksum(&mut text, style);
                 }
-                s
-            },
-            Err(e) => format!("Error fetching insider info: {}", e)
+                text
+            }));
+            out
+        }
+        OutputFormat::Json if ranking.is_some() => {
+            let (by, entries) = ranking.unwrap_or_default();
+            let mut json = serde_json::to_string_pretty(&serde_json::json!({ "rank_by": by, "ranking": entries, "packets": packets }))?;
+            json.push('\n');
+            json
+        }
+        OutputFormat::Json if packets.len() == 1 => render_packet_with_style(&packets[0], PacketFormat::Json, style),
+        OutputFormat::Json => {
+            let mut json = serde_json::to_string_pretty(packets)?;
+            json.push('\n');
+            json
         }
+        OutputFormat::Ndjson => {
+            let mut out = ranking.map(|(by, entries)| render_ranking_ndjson(entries, by)).unwrap_or_default();
+            out.extend(packets.iter().map(|p| render_packet_with_style(p, PacketFormat::Ndjson, style)));
+            out
+        }
+    })
+}
+
+/// Creates `path` and its parent directories. Refuses to clobber unless `force`.
+fn create_output(path: &str, force: bool) -> Result<File> {
+    let path = Path::new(path);
+    if path.exists() && !force {
+        anyhow::bail!("output file {} already exists (use --force to overwrite)", path.display());
+    }
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).with_context(|| format!("failed to create directory {}", parent.display()))?;
+    }
+    File::create(path).with_context(|| format!("failed to create output file {}", path.display()))
+}
+
+/// Writes `content` to `path` via `create_output`.
+/// Compresses with gzip when `gzip` is set or the path ends in `.gz`.
+fn write_output(path: &str, content: &str, force: bool, gzip: bool) -> Result<()> {
+    let gzip = gzip || path.ends_with(".gz");
+    let mut f = create_output(path, force)?;
+    if gzip {
+        let mut enc = GzEncoder::new(f, Compression::default());
+        enc.write_all(content.as_bytes())?;
+        // finish() writes the gzip trailer; dropping the encoder would swallow any error
+        enc.finish()?;
     } else {
-        String::new()
-    };
+        f.write_all(content.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Appends `content` to `path`, creating it and its parent directories if needed.
+fn append_output(path: &str, content: &str) -> Result<()> {
+    let path = Path::new(path);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).with_context(|| format!("failed to create directory {}", parent.display()))?;
+    }
+    let mut f = OpenOptions::new().create(true).append(true).open(path).with_context(|| format!("failed to open output file {}", path.display()))?;
+    f.write_all(content.as_bytes())?;
+    Ok(())
+}
+
+/// `--incremental` state file for `ticker`: the `ts_local` of the last bar exported, RFC 3339.
+fn state_path(state_dir: &str, ticker: &str) -> PathBuf {
+    Path::new(state_dir).join(format!("{}.last", ticker))
+}
+
+fn load_last_ts(state_dir: &str, ticker: &str) -> Result<Option<DateTime<FixedOffset>>> {
+    let path = state_path(state_dir, ticker);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(&path).with_context(|| format!("failed to read state file {}", path.display()))?;
+    let ts = DateTime::parse_from_rfc3339(text.trim()).with_context(|| format!("bad timestamp in state file {}", path.display()))?;
+    Ok(Some(ts))
+}
+
+/// Records the newest of the exported `bars`; leaves the state alone when there are none.
+fn store_last_ts(state_dir: &str, ticker: &str, bars: &[HourBar]) -> Result<()> {
+    let Some(last) = bars.iter().map(|b| b.ts_local).max() else { return Ok(()) };
+    fs::create_dir_all(state_dir).with_context(|| format!("failed to create state directory {}", state_dir))?;
+    let path = state_path(state_dir, ticker);
+    fs::write(&path, format!("{}\n", last.to_rfc3339())).with_context(|| format!("failed to write state file {}", path.display()))
+}
+
+/// Cuts each packet down to the bars after its last run. The sections are dropped too, so
+/// repeated runs append only bar records.
+fn keep_new_bars(packets: &mut [TickerPacket], state_dir: &str) -> Result<()> {
+    for p in packets {
+        let last = load_last_ts(state_dir, &p.ticker)?;
+        let before = p.bars.len();
+        retain_new_bars(&mut p.bars, last);
+        log::info!("{}: {} of {} bar(s) to export", p.ticker, p.bars.len(), before);
+        p.news = Section::Skipped;
+        p.insider = Section::Skipped;
+        p.senate = Section::Skipped;
+        p.finance = Section::Skipped;
+    }
+    Ok(())
+}
+
+/// Keeps the bars newer than `last`, minus the newest one when it is partial (a bucket still
+/// in progress at fetch time): exporting it would move the state past it, and its completed
+/// version would never be written. A partial bar that isn't the newest is final, as its day
+/// is over, and is kept.
+fn retain_new_bars(bars: &mut Vec<HourBar>, last: Option<DateTime<FixedOffset>>) {
+    if let Some(last) = last {
+        bars.retain(|b| b.ts_local > last);
+    }
+    let in_progress = bars.iter().max_by_key(|b| b.ts_local).filter(|b| b.is_partial).map(|b| b.ts_local);
+    if let Some(ts) = in_progress {
+        log::info!("holding back the bar at {} until it completes", ts.to_rfc3339());
+        bars.retain(|b| b.ts_local != ts);
+    }
+}
+
+/// Writes the packets' price bars to `path` as Parquet.
+#[cfg(feature = "parquet")]
+fn write_parquet(path: &str, packets: &[TickerPacket], force: bool) -> Result<()> {
+    let file = create_output(path, force)?;
+    let groups: Vec<(&str, &[HourBar])> = packets.iter().map(|p| (p.ticker.as_str(), p.bars.as_slice())).collect();
+    let tz = packets.first().map(|p| p.tz.as_str()).unwrap_or("UTC");
+    weekchart::bars_parquet::write_bars(file, tz, &groups).with_context(|| format!("failed to write Parquet file {}", path))
+}
+
+#[cfg(not(feature = "parquet"))]
+fn write_parquet(_path: &str, _packets: &[TickerPacket], _force: bool) -> Result<()> {
+    anyhow::bail!("this build has no Parquet support; rebuild with `cargo build --features parquet`")
+}
+
+/// Writes one output file (plus the `.txt` sidecar for Parquet) for `packets`.
+fn emit(path: &str, packets: &[TickerPacket], args: &Args, ranking: Ranking) -> Result<()> {
+    match args.format {
+        OutputFormat::Parquet => {
+            write_parquet(path, packets, args.force)?;
+            let sidecar = Path::new(path).with_extension("txt");
+            write_output(&sidecar.to_string_lossy(), &render(packets, args.format, args, ranking)?, args.force, args.gzip)
+        },
+        format => write_output(path, &render(packets, format, args, ranking)?, args.force, args.gzip),
+    }
+}
 
-    let finance_block = if !args_cli.no_finance {
-        let col = YahooSnapshotCollector;
-        match col.collect_snapshot(&ticker, meta.as_ref()) {
-            Ok(Some(s)) => {
-                format!(
-                    "source: {}\nasof_utc: {}\nprice_last: {}\nnotes: \"{}\"\n",
-                    s.source, s.asof_utc, s.price_last, s.notes
-                )
-            },
-            Ok(None) => "No snapshot available.".to_string(),
-            Err(e) => format!("Error fetching snapshot: {}", e)
+/// Writes the `--manifest` file, if asked for, once the packets themselves are out.
+fn write_manifest(packets: &[TickerPacket], failed: &[(String, String)], args: &Args) -> Result<()> {
+    let Some(path) = &args.manifest else { return Ok(()) };
+    write_output(path, &manifest_json(packets, failed, args)?, args.force, false).with_context(|| format!("failed to write manifest {}", path))
+}
+
+/// `--manifest`: one entry per packet with its coverage, the status of each optional section
+/// and where it was written, plus the symbols that produced no packet. A packet is `complete`
+/// when no requested section errored.
+fn manifest_json(packets: &[TickerPacket], failed: &[(String, String)], args: &Args) -> Result<String> {
+    let entries: Vec<_> = packets.iter().map(|p| {
+        let sections = [("news", p.news.status()), ("insider", p.insider.status()), ("senate", p.senate.status()), ("finance", p.finance.status())];
+        let (oldest, newest) = match p.bar_order {
+            BarOrder::Asc => (p.bars.first(), p.bars.last()),
+            BarOrder::Desc => (p.bars.last(), p.bars.first()),
+        };
+        let mut entry = serde_json::json!({
+            "ticker": p.ticker,
+            "bars_count": p.bars.len(),
+            "window_days": p.window_days,
+            "bar_size": p.bar_size,
+            "first_bar": oldest.map(|b| b.ts_local.to_rfc3339()),
+            "last_bar": newest.map(|b| b.ts_local.to_rfc3339()),
+            "complete": !p.bars.is_empty() && sections.iter().all(|(_, status)| *status != "error"),
+            "output": args.output.as_ref().map(|path| path.replace("{ticker}", &p.ticker)),
+        });
+        for (name, status) in sections {
+            entry[name] = status.into();
         }
-    } else {
-        String::new()
+        entry
+    }).collect();
+    let failed: Vec<_> = failed.iter().map(|(ticker, error)| serde_json::json!({ "ticker": ticker, "error": error })).collect();
+    let mut json = serde_json::to_string_pretty(&serde_json::json!({
+        "generated_utc": chrono::Utc::now().to_rfc3339(),
+        "packets": entries,
+        "failed": failed,
+    }))?;
+    json.push('\n');
+    Ok(json)
+}
+
+/// Builds the packet from `make()`; with `--fallback-csv`, a failed live fetch is retried from
+/// the first local candidate CSV, with a NOTES warning naming the file and its age.
+fn build_with_fallback(ticker: &str, args: &Args, make: impl Fn() -> PacketConfig) -> Result<TickerPacket> {
+    let err = match build_packet(make()) {
+        Ok(packet) => return Ok(packet),
+        Err(e) if args.fallback_csv && is_fetch_failure(&e) => e,
+        Err(e) => return Err(e),
+    };
+    let Some(path) = local_csv_candidates(ticker, &args.data_dirs).into_iter().find(|p| Path::new(p).is_file()) else {
+        return Err(err.context("no local CSV to fall back to"));
     };
+    let cause = err.root_cause().to_string();
+    log::warn!("live fetch for {} failed ({}); falling back to {}", ticker, cause, path);
+    let modified = fs::metadata(&path).and_then(|m| m.modified())
+        .map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+        .unwrap_or_else(|_| "unknown".to_string());
+    let config = PacketConfig {
+        source: PriceSource::Csv { path: path.clone(), assume_tz: args.assume_tz, columns: ColumnMap::from(&args.columns) },
+        ..make()
+    };
+    let mut packet = build_packet(config).with_context(|| format!("live fetch failed ({}) and so did the fallback", cause))?;
+    packet.notes.push(format!("WARNING: live price fetch failed ({}); bars are from {} (last modified {}) and may be stale", cause, path, modified));
+    Ok(packet)
+}
 
+/// True when the error came from the price provider (network, HTTP, upstream or response
+/// errors), as opposed to a problem with the bars themselves.
+fn is_fetch_failure(e: &anyhow::Error) -> bool {
+    use weekchart::error::ScrapyError;
+    matches!(
+        e.downcast_ref::<ScrapyError>(),
+        Some(ScrapyError::Network(_) | ScrapyError::Http(_) | ScrapyError::Provider { .. } | ScrapyError::NoData
+            | ScrapyError::RateLimited { .. } | ScrapyError::RetriesExhausted { .. } | ScrapyError::Decode(_) | ScrapyError::Json(_))
+    )
+}
 
-    // 4. Build Packet String
-    let mut packet = String::new();
-    packet.push_str("<<<TICKER_PACKET_V1>>>\n");
-    packet.push_str(&format!("TICKER: {}\n", ticker));
-    packet.push_str("TZ: America/New_York\n");
-    packet.push_str("SESSION: REGULAR (09:30-16:00)\n");
-    packet.push_str(&format!("WINDOW_DAYS: {}\n", args_cli.window_days));
-    packet.push_str("BAR_SIZE: 1h\n");
-    packet.push_str(&format!("BARS_COUNT: {}\n", chart.bars.len()));
-    packet.push_str("\n");
+/// `--top`: builds price-only packets for every symbol, ranks them by `--rank-by` and returns
+/// the full packets of the selected ones in rank order, plus the ranking of all of them.
+/// Symbols that fail to load or have no bars in the window are left out of the ranking.
+fn top_movers(tickers: &[String], n: usize, args: &Args, session: &SessionSpec, failed: &mut Vec<(String, String)>) -> Result<(Vec<TickerPacket>, Vec<RankEntry>)> {
+    let mut ranked = Vec::new();
+    for ticker in tickers {
+        let config = || PacketConfig { news: false, insider: false, senate: false, finance: false, ..packet_config(ticker, args, session) };
+        match build_with_fallback(ticker, args, config) {
+            Ok(p) if p.stats.pct_change.is_some() => ranked.push(p),
+            Ok(_) => {
+                log::warn!("skipping {}: no bars in the window to rank", ticker);
+                failed.push((ticker.clone(), "no bars in the window to rank".to_string()));
+            }
+            Err(e) => {
+                log::warn!("skipping {}: {:#}", ticker, e);
+                failed.push((ticker.clone(), format!("{:#}", e)));
+            }
+        }
+    }
+    match args.rank_by {
+        RankBy::Change => ranked.sort_by(|a, b| b.stats.pct_change.unwrap_or_default().total_cmp(&a.stats.pct_change.unwrap_or_default())),
+        RankBy::Volume => ranked.sort_by_key(|p| std::cmp::Reverse(p.stats.total_volume)),
+    }
+
+    // Gainers and losers both matter for change; only the busiest names for volume
+    let bottom = if matches!(args.rank_by, RankBy::Change) { n } else { 0 };
+    let total = ranked.len();
+    let selected = |i: usize| i < n || i + bottom >= total;
+    let entries = ranked.iter().enumerate().map(|(i, p)| RankEntry {
+        ticker: p.ticker.clone(),
+        pct_change: p.stats.pct_change,
+        total_volume: p.stats.total_volume,
+        selected: selected(i),
+    }).collect();
+
+    // The ranking pass skipped the collectors; rebuild the chosen few only if any are wanted
+    let collectors = !(args.no_news && args.no_insider && args.no_senate && args.no_finance);
+    let mut packets = Vec::new();
+    for (i, p) in ranked.into_iter().enumerate().filter(|(i, _)| selected(*i)) {
+        if !collectors {
+            packets.push(p);
+            continue;
+        }
+        match build_with_fallback(&p.ticker, args, || packet_config(&p.ticker, args, session)) {
+            Ok(full) => packets.push(full),
+            Err(e) => {
+                log::warn!("skipping {} (rank {}): {:#}", p.ticker, i + 1, e);
+                failed.push((p.ticker.clone(), format!("{:#}", e)));
+            }
+        }
+    }
+    Ok((packets, entries))
+}
+
+/// `weekchart serve`: builds one packet per request with the CLI flags, `window_days` and
+/// `format` taken from the query.
+fn serve(bind: &str, port: u16, packet_ttl: u64, args: &Args, session: &SessionSpec) -> Result<()> {
+    let listener = TcpListener::bind((bind, port)).with_context(|| format!("failed to listen on {}:{}", bind, port))?;
+    log::info!("serving packets on http://{}/packet?ticker=...", listener.local_addr()?);
+    server::serve(listener, Duration::from_secs(packet_ttl), |req: &PacketRequest| {
+        let config = || {
+            let mut config = packet_config(&req.ticker, args, session);
+            if let Some(days) = req.window_days {
+                config.window_days = days;
+            }
+            config
+        };
+        let format = match req.format {
+            PacketFormat::Text => OutputFormat::Text,
+            PacketFormat::Json => OutputFormat::Json,
+            PacketFormat::Ndjson => OutputFormat::Ndjson,
+        };
+        build_with_fallback(&req.ticker, args, config)
+            .and_then(|packet| render(std::slice::from_ref(&packet), format, args, None))
+            .map_err(|e| format!("{:#}", e))
+    })
+    .context("server stopped")
+}
 
-    packet.push_str("<<<PRICE_BARS_1H_CSV>>>\n");
-    packet.push_str("# ts_local,o,h,l,c,v\n");
-    for b in &chart.bars {
-        packet.push_str(&format!("{},{:.6},{:.6},{:.6},{:.6},{}\n", b.ts_local, b.o, b.h, b.l, b.c, b.v));
+fn main() -> Result<()> {
+    let mut args_cli = Args::parse();
+    args_cli.run_deadline = args_cli.deadline.map(|secs| Instant::now() + Duration::from_secs(secs));
+    init_logging(&args_cli);
+    net::set_global_rps(args_cli.rps);
+    if let Some(ua) = &args_cli.user_agent {
+        net::set_user_agent(ua.clone());
+    }
+    if let Some(proxy) = &args_cli.proxy {
+        net::set_proxy(proxy).with_context(|| format!("invalid --proxy {}", proxy))?;
     }
-    packet.push_str("<<<END_PRICE_BARS_1H_CSV>>>\n");
-    packet.push_str("\n");
+    if let Some(path) = &args_cli.ca_cert {
+        net::set_ca_cert(path).with_context(|| format!("failed to load --ca-cert {}", path))?;
+    }
+    let is_interactive = args_cli.ticker.is_none() && args_cli.tickers_file.is_none();
 
-    packet.push_str("<<<NEWS_TOP10_BODY>>>\n");
-    if !news_block.is_empty() {
-        packet.push_str(&news_block);
-        packet.push_str("\n");
+    if matches!(args_cli.format, OutputFormat::Parquet) && args_cli.output.is_none() {
+        anyhow::bail!("--format parquet needs --output <FILE>");
+    }
+    if args_cli.incremental {
+        if !matches!(args_cli.format, OutputFormat::Ndjson) {
+            anyhow::bail!("--incremental needs --format ndjson");
+        }
+        if args_cli.gzip || args_cli.output.as_deref().is_some_and(|p| p.ends_with(".gz")) {
+            anyhow::bail!("--incremental appends to its output and can't gzip it");
+        }
+        if args_cli.top.is_some() {
+            anyhow::bail!("--incremental can't be combined with --top");
+        }
+    }
+    if let (Some(from), Some(to)) = (args_cli.from, args_cli.to) {
+        if from > to {
+            anyhow::bail!("--from {} is after --to {}", from, to);
+        }
+    }
+    let asset_class = AssetClass::from(args_cli.asset_class);
+    let kind = SessionKind::from(args_cli.session);
+    let (default_open, default_close) = match asset_class {
+        AssetClass::Futures => AssetClass::futures_hours(),
+        _ => kind.hours(),
+    };
+    let (open, close) = (args_cli.session_open.unwrap_or(default_open), args_cli.session_close.unwrap_or(default_close));
+    if open >= close {
+        anyhow::bail!("session open {} must be before close {}", open.format("%H:%M"), close.format("%H:%M"));
     }
-    packet.push_str("<<<END_NEWS_TOP10_BODY>>>\n");
-    packet.push_str("\n");
+    let session = match asset_class {
+        AssetClass::Crypto => SessionSpec::crypto(),
+        _ => SessionSpec {
+            asset_class,
+            kind,
+            tz: args_cli.session_tz,
+            open,
+            close,
+            nyse_half_days: asset_class == AssetClass::Equity && args_cli.session_tz == chrono_tz::America::New_York,
+            calendar: TradingCalendar::new(args_cli.holidays.iter().copied()),
+        },
+    };
 
-    packet.push_str("<<<INSIDER_AND_INSTITUTIONAL_ACTIVITY>>>\n");
-     if !insider_block.is_empty() {
-        packet.push_str(&insider_block);
-        packet.push_str("\n");
+    if let Some(Command::Serve { port, bind, packet_ttl }) = &args_cli.command {
+        if args_cli.source_path.as_deref() == Some(STDIN_PATH) {
+            anyhow::bail!("serve can't read prices from stdin; use --source-path with {{ticker}} or a provider");
+        }
+        return serve(bind, *port, *packet_ttl, &args_cli, &session);
     }
-    packet.push_str("<<<END_INSIDER_AND_INSTITUTIONAL_ACTIVITY>>>\n");
-    packet.push_str("\n");
 
-    packet.push_str("<<<FINANCE_SNAPSHOT>>>\n");
-    if !finance_block.is_empty() {
-        packet.push_str(&finance_block);
+    // stdin carries the price data, so there are no prompts and only one symbol to feed
+    if args_cli.source_path.as_deref() == Some(STDIN_PATH) {
+        if is_interactive {
+            anyhow::bail!("--source-path - needs --ticker");
+        }
+        let count = collect_tickers(&args_cli)?.len();
+        if count > 1 {
+            anyhow::bail!("--source-path - can feed only one symbol, got {}", count);
+        }
     }
-    packet.push_str("<<<END_FINANCE_SNAPSHOT>>>\n");
-    packet.push_str("\n");
 
-    // 5. Output Handling
-    print!("{}", packet);
+    if args_cli.check {
+        if is_interactive {
+            anyhow::bail!("--check needs --ticker or --tickers-file");
+        }
+        for ticker in collect_tickers(&args_cli)? {
+            check_inputs(&ticker, &args_cli, &session)?;
+        }
+        println!("OK");
+        return Ok(());
+    }
 
-    let output_file = if let Some(path) = args_cli.output {
-        Some(path)
-    } else if is_interactive {
-        Some(format!("{}_packet.txt", ticker))
+    // Interactive Mode Logic
+    let tickers = if is_interactive {
+        let t = prompt_input("Enter Ticker (e.g. AMZN): ")?;
+        if t.is_empty() {
+            anyhow::bail!("Ticker cannot be empty");
+        }
+        vec![t.to_uppercase()]
     } else {
-        None
+        collect_tickers(&args_cli)?
     };
 
-    if let Some(path) = output_file {
+    // Interactive runs without --source-path use a local CSV if one exists, else ask
+    if is_interactive && args_cli.source_path.is_none() {
+        let ticker = &tickers[0];
+        match local_csv_candidates(ticker, &args_cli.data_dirs).into_iter().find(|p| Path::new(p).is_file()) {
+            Some(path) => {
+                log::info!("Found data at: {}", path);
+                args_cli.source_path = Some(path);
+            }
+            None => {
+                let answer = prompt_input(&format!("No CSV found for {}. Enter a CSV path, or [F]etch from Yahoo (default): ", ticker))?;
+                if answer.is_empty() || answer.eq_ignore_ascii_case("f") {
+                    args_cli.provider = ProviderKind::Yahoo;
+                    if args_cli.save_csv.is_none() {
+                        args_cli.save_csv = local_csv_candidates(ticker, &args_cli.data_dirs).into_iter().next();
+                    }
+                } else {
+                    args_cli.source_path = Some(answer);
+                }
+            }
+        }
+    }
+
+    if is_interactive {
+        log::info!("Fetching data for {} from the internet...", tickers[0]);
+        log::info!("(This may take a few seconds to scrape news bodies and insider info)");
+    }
+
+    // One packet per symbol; in batch mode a failing symbol is skipped with a warning
+    let mut packets = Vec::new();
+    let mut rank_entries = Vec::new();
+    let mut failed = Vec::new();
+    if let Some(n) = args_cli.top {
+        (packets, rank_entries) = top_movers(&tickers, n, &args_cli, &session, &mut failed)?;
+    } else {
+        for ticker in &tickers {
+            match build_with_fallback(ticker, &args_cli, || packet_config(ticker, &args_cli, &session)) {
+                Ok(packet) => packets.push(packet),
+                Err(e) if tickers.len() > 1 => {
+                    log::warn!("skipping {}: {:#}", ticker, e);
+                    failed.push((ticker.clone(), format!("{:#}", e)));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    let ranking = args_cli.top.map(|_| (args_cli.rank_by.label(), rank_entries.as_slice()));
+    if packets.is_empty() {
+        write_manifest(&packets, &failed, &args_cli)?;
+        anyhow::bail!("No packets produced for {}", tickers.join(", "));
+    }
+    // Incremental exports append only the new bars, then move each symbol's state forward
+    if let Some(state_dir) = args_cli.state_dir.as_deref().filter(|_| args_cli.incremental) {
+        keep_new_bars(&mut packets, state_dir)?;
+        for p in &packets {
+            let content = render(std::slice::from_ref(p), args_cli.format, &args_cli, None)?;
+            match &args_cli.output {
+                Some(path) => append_output(&path.replace("{ticker}", &p.ticker), &content)?,
+                None => print!("{}", content),
+            }
+            store_last_ts(state_dir, &p.ticker, &p.bars)?;
+        }
+        return write_manifest(&packets, &failed, &args_cli);
+    }
+    // 5. Output Handling
+    if let Some(path) = &args_cli.output {
+        // `{ticker}` in the path splits a batch into one file per symbol
+        if path.contains("{ticker}") {
+            for p in &packets {
+                emit(&path.replace("{ticker}", &p.ticker), std::slice::from_ref(p), &args_cli, None)?;
+            }
+            if ranking.is_some() {
+                // The ranking spans every symbol, so it gets a file of its own
+                let format = match args_cli.format {
+                    OutputFormat::Parquet => OutputFormat::Text,
+                    format => format,
+                };
+                let content = render(&[], format, &args_cli, ranking)?;
+                write_output(&path.replace("{ticker}", "RANKING"), &content, args_cli.force, args_cli.gzip)?;
+            }
+        } else {
+            emit(path, &packets, &args_cli, ranking)?;
+        }
+        return write_manifest(&packets, &failed, &args_cli);
+    }
+
+    let packet = render(&packets, args_cli.format, &args_cli, ranking)?;
+    print!("{}", packet);
+
+    if is_interactive {
+        let path = format!("{}_packet.txt", tickers[0]);
         let mut f = File::create(&path).with_context(|| format!("failed to create output file {}", path))?;
         f.write_all(packet.as_bytes())?;
-        if is_interactive {
-            eprintln!("Packet saved to: {}", path);
-        }
+        log::info!("Packet saved to: {}", path);
     }
 
-    Ok(())
+    write_manifest(&packets, &failed, &args_cli)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use weekchart::market::{resample_1h_regular_session, MinuteBar};
+
+    /// One-minute bars from the 2024-02-12 open through `hh:mm` local (EST).
+    fn bars_until(hh: u32, mm: u32) -> Vec<HourBar> {
+        let open: chrono::DateTime<chrono::Utc> = "2024-02-12T14:30:00Z".parse().unwrap();
+        let count = (hh as i64 * 60 + mm as i64) - (9 * 60 + 30) + 1;
+        let minutes: Vec<MinuteBar> = (0..count)
+            .map(|i| MinuteBar { ts_utc: open + chrono::Duration::minutes(i), o: 1.0, h: 1.0, l: 1.0, c: 1.0, v: 1, adj_c: None })
+            .collect();
+        resample_1h_regular_session("T", &minutes, 1).bars
+    }
+
+    fn local_times(bars: &[HourBar]) -> Vec<String> {
+        bars.iter().map(|b| b.ts_local.format("%H:%M").to_string()).collect()
+    }
+
+    #[test]
+    fn incremental_runs_hold_back_the_bar_in_progress() {
+        let state_dir = std::env::temp_dir().join(format!("weekchart-incremental-{}", std::process::id()));
+        let state_dir = state_dir.to_str().unwrap();
+        let _ = fs::remove_dir_all(state_dir);
+
+        // 10:45: the 10:30 bucket has 15 of its 60 minutes and waits for the next run
+        let mut first = bars_until(10, 44);
+        retain_new_bars(&mut first, load_last_ts(state_dir, "T").unwrap());
+        assert_eq!(local_times(&first), ["09:30"]);
+        store_last_ts(state_dir, "T", &first).unwrap();
+
+        // 11:45: the now complete 10:30 bar goes out, 11:30 is held back in turn
+        let mut second = bars_until(11, 44);
+        retain_new_bars(&mut second, load_last_ts(state_dir, "T").unwrap());
+        assert_eq!(local_times(&second), ["10:30"]);
+        assert_eq!(second[0].sample_count, 60);
+        store_last_ts(state_dir, "T", &second).unwrap();
+
+        // After the close nothing is partial and the rest of the day is exported
+        let mut third = bars_until(15, 59);
+        retain_new_bars(&mut third, load_last_ts(state_dir, "T").unwrap());
+        assert_eq!(local_times(&third), ["11:30", "12:30", "13:30", "14:30", "15:30"]);
+
+        fs::remove_dir_all(state_dir).unwrap();
+    }
 }
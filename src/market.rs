@@ -1,9 +1,11 @@
-use chrono::{DateTime, NaiveDate, NaiveDateTime, Timelike, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Timelike, Utc, Weekday};
 use chrono_tz::America::New_York;
 use chrono_tz::Tz;
-use std::collections::BTreeMap;
+use crate::error::{Result, ScrapyError};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct MinuteBar {
     pub ts_utc: DateTime<Utc>,
     pub o: f64,
@@ -11,119 +13,1150 @@ pub struct MinuteBar {
     pub l: f64,
     pub c: f64,
     pub v: u64,
+    pub adj_c: Option<f64>, // split/dividend-adjusted close, when the provider supplies it
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct HourBar {
-    pub ts_local: String, // RFC3339 in America/New_York
+    pub ts_local: DateTime<Tz>, // bucket start in the session timezone
     pub o: f64,
     pub h: f64,
     pub l: f64,
     pub c: f64,
     pub v: u64,
+    pub vwap: f64, // typical price (h+l+c)/3 weighted by volume
+    pub notional: f64, // sum(c * v) over the minute bars: traded value in quote currency
+    pub sample_count: u32, // minute bars folded into this bar
+    pub adj_c: Option<f64>, // adjusted close of the last minute bar, if any
+    pub synthetic: bool, // forward-filled by `fill_missing_buckets`, no real prints
+    pub is_partial: bool, // last bucket of its day with fewer prints than the input interval fits in it
+    pub ret_simple: Option<f64>, // c / prev c - 1, set by `add_returns`
+    pub ret_log: Option<f64>, // ln(c / prev c), set by `add_returns`
+    pub sma: Option<f64>, // rolling mean of recent closes, set by `add_sma`
+    pub ema: Option<f64>, // exponential moving average of closes, set by `add_ema`
 }
 
 #[derive(Debug, Clone)]
 pub struct PriceChart1H {
     pub ticker: String,
     pub window_days: i64,
+    pub bar_minutes: u32,
+    pub session: SessionSpec,
     pub bars: Vec<HourBar>,
+    pub report: ResampleReport,
+}
+
+/// How many minute bars and days made it through resampling, to tell an empty input apart
+/// from one whose bars all fell outside the session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResampleReport {
+    /// Minute bars handed to the resampler
+    pub input_bars: usize,
+    /// Of those, the ones inside the session on a trading day
+    pub in_session_bars: usize,
+    /// Trading days with at least one in-session bar
+    pub days_seen: usize,
+    /// Days left after the window or date range was applied
+    pub days_kept: usize,
+}
+
+/// Which part of the US trading day a session covers. Sets the default hours and the packet's
+/// `SESSION:` label; `SessionSpec::open`/`close` can still be moved independently.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SessionKind {
+    /// 09:30-16:00
+    #[default]
+    Regular,
+    /// 04:00-09:30
+    PreMarket,
+    /// 16:00-20:00
+    AfterHours,
+    /// 04:00-20:00, pre-market through after-hours without a break
+    Extended,
+}
+
+impl SessionKind {
+    /// Default local (open, close) for this kind, in exchange time.
+    pub fn hours(self) -> (NaiveTime, NaiveTime) {
+        let hm = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        match self {
+            SessionKind::Regular => (hm(9, 30), hm(16, 0)),
+            SessionKind::PreMarket => (hm(4, 0), hm(9, 30)),
+            SessionKind::AfterHours => (hm(16, 0), hm(20, 0)),
+            SessionKind::Extended => (hm(4, 0), hm(20, 0)),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SessionKind::Regular => "REGULAR",
+            SessionKind::PreMarket => "PRE_MARKET",
+            SessionKind::AfterHours => "AFTER_HOURS",
+            SessionKind::Extended => "EXTENDED",
+        }
+    }
+}
+
+/// What kind of instrument the minutes belong to, which picks the session rule.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AssetClass {
+    /// Exchange hours from `SessionKind`, weekends and holidays off, NYSE half-days
+    #[default]
+    Equity,
+    /// Trades around the clock every day: no session filter, buckets on a UTC grid from 00:00
+    Crypto,
+    /// A near-24h session set by `SessionSpec::open`/`close` (within one local day), weekdays only
+    Futures,
+}
+
+impl AssetClass {
+    /// Default futures hours: one session per local day with a one-hour break before midnight.
+    pub fn futures_hours() -> (NaiveTime, NaiveTime) {
+        (NaiveTime::MIN, NaiveTime::from_hms_opt(23, 0, 0).unwrap())
+    }
+}
+
+/// Trading session definition: local timezone plus open (inclusive) and close (exclusive).
+#[derive(Debug, Clone)]
+pub struct SessionSpec {
+    pub asset_class: AssetClass,
+    pub kind: SessionKind,
+    pub tz: Tz,
+    pub open: NaiveTime,
+    pub close: NaiveTime,
+    pub nyse_half_days: bool, // apply NYSE 13:00 early closes (see `is_early_close`)
+    pub calendar: TradingCalendar,
+}
+
+/// Trading-day calendar: weekends are never trading days, plus a configurable holiday set.
+#[derive(Debug, Clone, Default)]
+pub struct TradingCalendar {
+    holidays: BTreeSet<NaiveDate>,
+}
+
+impl TradingCalendar {
+    pub fn new(holidays: impl IntoIterator<Item = NaiveDate>) -> Self {
+        TradingCalendar { holidays: holidays.into_iter().collect() }
+    }
+
+    pub fn is_trading_day(&self, date: NaiveDate) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !self.holidays.contains(&date)
+    }
+}
+
+impl Default for SessionSpec {
+    /// US regular session, 09:30-16:00 America/New_York.
+    fn default() -> Self {
+        SessionSpec::us(SessionKind::Regular)
+    }
+}
+
+impl SessionSpec {
+    /// A US session of the given kind in America/New_York, with NYSE half-days.
+    pub fn us(kind: SessionKind) -> Self {
+        let (open, close) = kind.hours();
+        SessionSpec {
+            asset_class: AssetClass::Equity,
+            kind,
+            tz: New_York,
+            open,
+            close,
+            nyse_half_days: true,
+            calendar: TradingCalendar::default(),
+        }
+    }
+
+    /// Round-the-clock UTC session for crypto, every day of the week.
+    pub fn crypto() -> Self {
+        SessionSpec {
+            asset_class: AssetClass::Crypto,
+            kind: SessionKind::Regular,
+            tz: chrono_tz::UTC,
+            open: NaiveTime::MIN,
+            close: NaiveTime::MIN,
+            nyse_half_days: false,
+            calendar: TradingCalendar::default(),
+        }
+    }
+
+    /// Session window as shown in the packet header, e.g. "09:30-16:00".
+    pub fn hours_label(&self) -> String {
+        if self.asset_class == AssetClass::Crypto {
+            return "00:00-24:00".to_string();
+        }
+        format!("{}-{}", self.open.format("%H:%M"), self.close.format("%H:%M"))
+    }
+
+    /// The packet's `SESSION:` value, e.g. "REGULAR (09:30-16:00)", "FUTURES (00:00-23:00)" or "24H".
+    pub fn label(&self) -> String {
+        match self.asset_class {
+            AssetClass::Equity => format!("{} ({})", self.kind.label(), self.hours_label()),
+            AssetClass::Crypto => "24H".to_string(),
+            AssetClass::Futures => format!("FUTURES ({})", self.hours_label()),
+        }
+    }
+
+    /// Crypto trades every day; everything else follows the calendar.
+    pub fn is_trading_day(&self, date: NaiveDate) -> bool {
+        self.asset_class == AssetClass::Crypto || self.calendar.is_trading_day(date)
+    }
+
+    /// Minutes from local time `t` on `date` to that day's close (to midnight for crypto).
+    pub fn minutes_to_close(&self, date: NaiveDate, t: NaiveTime) -> i64 {
+        if self.asset_class == AssetClass::Crypto {
+            return 24 * 60 - (t - NaiveTime::MIN).num_minutes();
+        }
+        (self.close_on(date) - t).num_minutes()
+    }
+
+    /// Close time for a given local date, taking half-days into account. Only the regular
+    /// equity session is cut short; extended-hours trading on half-days isn't modelled.
+    pub fn close_on(&self, date: NaiveDate) -> NaiveTime {
+        if self.nyse_half_days && self.asset_class == AssetClass::Equity && self.kind == SessionKind::Regular {
+            if let Some(early) = is_early_close(date) {
+                return early.min(self.close);
+            }
+        }
+        self.close
+    }
+}
+
+/// Returns the early close time (13:00 ET) if `date` is a regular NYSE half-day:
+/// the day before Independence Day, the day after Thanksgiving, and Christmas Eve.
+/// July 3 and December 24 only close early on Mon-Thu; on a Friday they are either
+/// a full holiday (observed) or followed by a weekend.
+pub fn is_early_close(date: NaiveDate) -> Option<NaiveTime> {
+    let early = NaiveTime::from_hms_opt(13, 0, 0);
+    let mon_thu = matches!(date.weekday(), Weekday::Mon | Weekday::Tue | Weekday::Wed | Weekday::Thu);
+    match (date.month(), date.day()) {
+        (7, 3) | (12, 24) if mon_thu => early,
+        (11, _) if date.weekday() == Weekday::Fri => {
+            // Thanksgiving is the 4th Thursday of November, i.e. Nov 22-28, so the Friday after is Nov 23-29
+            if (23..=29).contains(&date.day()) { early } else { None }
+        }
+        _ => None,
+    }
 }
 
 /// Resamples minute bars into 1-hour bars for the regular US session (09:30-16:00 ET).
 /// Only the last `window_days` trading days are included.
 pub fn resample_1h_regular_session(ticker: &str, minutes: &[MinuteBar], window_days: i64) -> PriceChart1H {
-    // 1. Group strictly VALID bars by Trading Day (Local Date)
-    // Using BTreeMap to keep days sorted
-    let mut by_day: BTreeMap<NaiveDate, Vec<&MinuteBar>> = BTreeMap::new();
-    for b in minutes {
-        let local = b.ts_utc.with_timezone(&New_York);
-        if is_regular_session(&local) {
-             by_day.entry(local.date_naive()).or_default().push(b);
-        }
-    }
+    resample_regular_session(ticker, minutes, window_days, 60)
+}
+
+/// Resamples minute bars into `bucket_minutes` bars for the regular US session (09:30-16:00 ET).
+/// Buckets are anchored at 09:30. When `bucket_minutes` does not divide the 390-minute
+/// session evenly, the final bar per day may be shorter.
+pub fn resample_regular_session(ticker: &str, minutes: &[MinuteBar], window_days: i64, bucket_minutes: u32) -> PriceChart1H {
+    resample_session(ticker, minutes, window_days, bucket_minutes, &SessionSpec::default())
+}
+
+/// Resamples minute bars into `bucket_minutes` bars for an arbitrary session.
+/// Days are grouped by the local date in `spec.tz` and buckets are anchored at `spec.open`.
+pub fn resample_session(ticker: &str, minutes: &[MinuteBar], window_days: i64, bucket_minutes: u32, spec: &SessionSpec) -> PriceChart1H {
+    let mut chart = resample_selected_days(ticker, minutes, bucket_minutes, spec, |days| select_last_days(days, window_days));
+    chart.window_days = window_days;
+    chart
+}
+
+/// Like `resample_session`, but keeps the trading days in `[from, to]` (inclusive, local
+/// dates) instead of the last N. `window_days` on the result is the number of days kept.
+pub fn resample_session_range(ticker: &str, minutes: &[MinuteBar], from: NaiveDate, to: NaiveDate, bucket_minutes: u32, spec: &SessionSpec) -> PriceChart1H {
+    resample_selected_days(ticker, minutes, bucket_minutes, spec, |days| select_days_range(days, from, to))
+}
+
+/// `resample_session`, also returning for each output bar the indices into `minutes` of the
+/// minute bars folded into it, in input order. For auditing which prints made up a bar.
+pub fn resample_with_provenance(ticker: &str, minutes: &[MinuteBar], window_days: i64, bucket_minutes: u32, spec: &SessionSpec) -> (PriceChart1H, Vec<Vec<usize>>) {
+    let (mut chart, sources) = resample_traced(ticker, minutes, bucket_minutes, spec, |days| select_last_days(days, window_days));
+    chart.window_days = window_days;
+    (chart, sources)
+}
 
-    // 2. Select last N days
-    let days: Vec<NaiveDate> = by_day.keys().cloned().collect();
+/// The last `window_days` of the sorted `days`; a negative window keeps all of them.
+pub fn select_last_days(days: &[NaiveDate], window_days: i64) -> &[NaiveDate] {
     let start_idx = if days.len() > window_days as usize {
         days.len() - window_days as usize
     } else {
         0
     };
-    let keep_days = &days[start_idx..];
+    &days[start_idx..]
+}
+
+/// The sorted `days` falling within `[from, to]` inclusive; empty when `from > to`.
+pub fn select_days_range(days: &[NaiveDate], from: NaiveDate, to: NaiveDate) -> &[NaiveDate] {
+    let start = days.partition_point(|d| *d < from);
+    let end = days.partition_point(|d| *d <= to).max(start);
+    &days[start..end]
+}
+
+fn resample_selected_days(
+    ticker: &str,
+    minutes: &[MinuteBar],
+    bucket_minutes: u32,
+    spec: &SessionSpec,
+    select: impl Fn(&[NaiveDate]) -> &[NaiveDate],
+) -> PriceChart1H {
+    resample_traced(ticker, minutes, bucket_minutes, spec, select).0
+}
+
+/// `resample_selected_days` plus, per bar, the indices of the minutes folded into it.
+fn resample_traced(
+    ticker: &str,
+    minutes: &[MinuteBar],
+    bucket_minutes: u32,
+    spec: &SessionSpec,
+    select: impl Fn(&[NaiveDate]) -> &[NaiveDate],
+) -> (PriceChart1H, Vec<Vec<usize>>) {
+    let bucket_minutes = bucket_minutes.max(1);
+
+    // 1. Group strictly VALID bars by Trading Day (Local Date)
+    // Using BTreeMap to keep days sorted
+    let mut by_day: BTreeMap<NaiveDate, Vec<(usize, &MinuteBar)>> = BTreeMap::new();
+    for (i, b) in minutes.iter().enumerate() {
+        let local = b.ts_utc.with_timezone(&spec.tz);
+        if is_regular_session(&local, spec) {
+             by_day.entry(local.date_naive()).or_default().push((i, b));
+        }
+    }
+
+    // 2. Select the trading days to keep (stray weekend/holiday prints don't count towards the window)
+    let days: Vec<NaiveDate> = by_day.keys().filter(|d| spec.is_trading_day(**d)).cloned().collect();
+    let keep_days = select(&days);
+    let report = ResampleReport {
+        input_bars: minutes.len(),
+        in_session_bars: days.iter().map(|d| by_day[d].len()).sum(),
+        days_seen: days.len(),
+        days_kept: keep_days.len(),
+    };
 
     // 3. Resample each day into hourly buckets
     let mut final_bars = Vec::new();
-
+    let mut sources = Vec::new();
     for day in keep_days {
         if let Some(day_minutes) = by_day.get(day) {
-             // Map BucketStart -> HourBar. BTreeMap ensures chronological order (09:30, 10:30, ...)
-             let mut day_buckets: BTreeMap<DateTime<Tz>, HourBar> = BTreeMap::new();
-             
-             for b in day_minutes {
-                 let local = b.ts_utc.with_timezone(&New_York);
-                 // Safety: is_regular_session already checked, so get_bucket_start shouldn't fail
-                 if let Some(bucket_start) = get_bucket_start(&local) {
-                     day_buckets
-                        .entry(bucket_start)
-                        .and_modify(|agg| {
-                            agg.h = agg.h.max(b.h);
-                            agg.l = agg.l.min(b.l);
-                            agg.c = b.c;   // Last bar processed becomes the close
-                            agg.v += b.v;
-                        })
-                        .or_insert(HourBar {
-                            ts_local: bucket_start.to_rfc3339(),
-                            o: b.o,
-                            h: b.h,
-                            l: b.l,
-                            c: b.c,
-                            v: b.v,
-                        });
-                 }
-             }
-             
-             // Append to final list in order
-             for (_, bar) in day_buckets {
-                 final_bars.push(bar);
-             }
-        }
-    }
-
-    PriceChart1H {
+            let (bars, day_sources) = resample_day(day_minutes, bucket_minutes, spec);
+            final_bars.extend(bars);
+            sources.extend(day_sources);
+        }
+    }
+
+    let chart = PriceChart1H {
         ticker: ticker.to_uppercase(),
-        window_days,
+        window_days: keep_days.len() as i64,
+        bar_minutes: bucket_minutes,
+        session: spec.clone(),
         bars: final_bars,
+        report,
+    };
+    (chart, sources)
+}
+
+/// Folds one local day's in-session minutes (in time order, each with its index in the
+/// input) into `bucket_minutes` bars, returned with the indices that went into each bar.
+fn resample_day(day_minutes: &[(usize, &MinuteBar)], bucket_minutes: u32, spec: &SessionSpec) -> (Vec<HourBar>, Vec<Vec<usize>>) {
+    // Map BucketStart -> HourBar. BTreeMap ensures chronological order (09:30, 10:30, ...)
+    let mut day_buckets: BTreeMap<DateTime<Tz>, (HourBar, Vec<usize>)> = BTreeMap::new();
+
+    for &(i, b) in day_minutes {
+        let local = b.ts_utc.with_timezone(&spec.tz);
+        let pv = (b.h + b.l + b.c) / 3.0 * b.v as f64;
+        // Summing a few hundred positive products in f64 keeps the relative error near 1e-14,
+        // far below a cent even on billion-dollar bars, so no compensated sum is needed
+        let cv = b.c * b.v as f64;
+        // Safety: is_regular_session already checked, so get_bucket_start shouldn't fail
+        if let Some(bucket_start) = get_bucket_start(&local, bucket_minutes, spec) {
+            day_buckets
+                .entry(bucket_start)
+                .and_modify(|(agg, sources)| {
+                    sources.push(i);
+                    agg.h = agg.h.max(b.h);
+                    agg.l = agg.l.min(b.l);
+                    agg.c = b.c;   // Last bar processed becomes the close
+                    agg.adj_c = b.adj_c;
+                    agg.v += b.v;
+                    agg.vwap += pv; // running sum(tp * v), normalized below
+                    agg.notional += cv;
+                    agg.sample_count += 1;
+                })
+                .or_insert((HourBar {
+                    ts_local: bucket_start,
+                    o: b.o,
+                    h: b.h,
+                    l: b.l,
+                    c: b.c,
+                    v: b.v,
+                    vwap: pv,
+                    notional: cv,
+                    sample_count: 1,
+                    adj_c: b.adj_c,
+                    synthetic: false,
+                    is_partial: false,
+                    ret_simple: None,
+                    ret_log: None,
+                    sma: None,
+                    ema: None,
+                }, vec![i]));
+        }
     }
+
+    let (mut bars, sources): (Vec<HourBar>, Vec<Vec<usize>>) = day_buckets.into_values().map(|(mut bar, sources)| {
+        bar.vwap = if bar.v > 0 { bar.vwap / bar.v as f64 } else { bar.c };
+        (bar, sources)
+    }).unzip();
+    // The last bucket is full when it has a print for every input interval up to the bucket's
+    // end or the day's close, whichever comes first (so 15:30-16:00 needs 30 one-minute bars or
+    // 6 five-minute ones, and half-days less). The interval is the day's tightest bar spacing
+    if let Some(last) = bars.last_mut() {
+        let step_secs = day_minutes.windows(2)
+            .map(|w| (w[1].1.ts_utc - w[0].1.ts_utc).num_seconds())
+            .filter(|secs| *secs > 0)
+            .min()
+            .unwrap_or(60)
+            .max(60) as u64;
+        let to_close = spec.minutes_to_close(last.ts_local.date_naive(), last.ts_local.time()).max(0) as u64;
+        let expected = (to_close.min(bucket_minutes as u64) * 60).div_ceil(step_secs);
+        last.is_partial = (last.sample_count as u64) < expected;
+    }
+    (bars, sources)
 }
 
-/// Returns true if the time is within 09:30:00 (inclusive) and 16:00:00 (exclusive).
-fn is_regular_session(dt: &DateTime<Tz>) -> bool {
-    let h = dt.hour();
-    let m = dt.minute();
-    // Pre-market: before 09:30
-    if h < 9 || (h == 9 && m < 30) { return false; }
-    // After-hours: 16:00 and later
-    if h >= 16 { return false; }
+/// An intraday hole in the minute data: (last bar before, first bar after), both UTC.
+pub type Gap = (DateTime<Utc>, DateTime<Utc>);
+
+/// Incremental `resample_session` for inputs too large to hold in memory. Minutes must be
+/// pushed in time order; only the current local day is buffered, and only the last
+/// `window_days` resampled days are kept. Gaps are tracked as in `find_gaps_in_session`.
+pub struct StreamingResampler {
+    ticker: String,
+    window_days: i64,
+    bucket_minutes: u32,
+    spec: SessionSpec,
+    max_gap_secs: Option<i64>,
+    range: Option<(NaiveDate, NaiveDate)>,
+    day: Option<NaiveDate>,
+    day_minutes: Vec<MinuteBar>,
+    days: VecDeque<Vec<HourBar>>,
+    last_in_session: Option<DateTime<Utc>>,
+    gaps: Vec<Gap>,
+    last_seen_day: Option<NaiveDate>,
+    report: ResampleReport,
+}
+
+impl StreamingResampler {
+    pub fn new(ticker: &str, window_days: i64, bucket_minutes: u32, spec: &SessionSpec, max_gap_secs: Option<i64>) -> Self {
+        Self {
+            ticker: ticker.to_uppercase(),
+            window_days,
+            bucket_minutes: bucket_minutes.max(1),
+            spec: spec.clone(),
+            max_gap_secs,
+            range: None,
+            day: None,
+            day_minutes: Vec::new(),
+            days: VecDeque::new(),
+            last_in_session: None,
+            gaps: Vec::new(),
+            last_seen_day: None,
+            report: ResampleReport::default(),
+        }
+    }
+
+    /// Keeps the trading days in `[from, to]` instead of the last `window_days`, as
+    /// `resample_session_range` does.
+    pub fn with_range(mut self, from: NaiveDate, to: NaiveDate) -> Self {
+        self.range = Some((from, to));
+        self
+    }
+
+    /// Adds the next minute. Bars outside the session or on non-trading days are ignored.
+    pub fn push(&mut self, bar: MinuteBar) {
+        let local = bar.ts_utc.with_timezone(&self.spec.tz);
+        let date = local.date_naive();
+        self.report.input_bars += 1;
+        if !is_regular_session(&local, &self.spec) || !self.spec.is_trading_day(date) {
+            return;
+        }
+        self.report.in_session_bars += 1;
+        if self.last_seen_day != Some(date) {
+            self.last_seen_day = Some(date);
+            self.report.days_seen += 1;
+        }
+        if self.range.is_some_and(|(from, to)| date < from || date > to) {
+            return;
+        }
+        if self.day != Some(date) {
+            self.flush_day();
+            self.day = Some(date);
+            self.last_in_session = None;
+        }
+        if let (Some(max), Some(prev)) = (self.max_gap_secs, self.last_in_session) {
+            if (bar.ts_utc - prev).num_seconds() > max {
+                self.gaps.push((prev, bar.ts_utc));
+            }
+        }
+        self.last_in_session = Some(bar.ts_utc);
+        self.day_minutes.push(bar);
+    }
+
+    fn flush_day(&mut self) {
+        if self.day_minutes.is_empty() {
+            return;
+        }
+        let refs: Vec<(usize, &MinuteBar)> = self.day_minutes.iter().enumerate().collect();
+        self.days.push_back(resample_day(&refs, self.bucket_minutes, &self.spec).0);
+        self.day_minutes.clear();
+        // A negative window keeps everything, as in `resample_session`
+        if let (None, Ok(keep)) = (self.range, usize::try_from(self.window_days)) {
+            while self.days.len() > keep {
+                self.days.pop_front();
+            }
+        }
+    }
+
+    /// Resamples the last day and returns the chart plus the gaps seen (empty unless
+    /// `max_gap_secs` was set).
+    pub fn finish(mut self) -> (PriceChart1H, Vec<Gap>) {
+        self.flush_day();
+        let report = ResampleReport { days_kept: self.days.len(), ..self.report };
+        let chart = PriceChart1H {
+            ticker: self.ticker,
+            window_days: if self.range.is_some() { self.days.len() as i64 } else { self.window_days },
+            bar_minutes: self.bucket_minutes,
+            session: self.spec,
+            bars: self.days.into_iter().flatten().collect(),
+            report,
+        };
+        (chart, self.gaps)
+    }
+}
+
+/// Inserts a synthetic bar for each bucket missing between the first and last real bar of
+/// every local day, so thin symbols still produce a fixed grid. Synthetic bars carry the
+/// previous bar's close as o/h/l/c/vwap with zero volume and `sample_count`. Buckets before
+/// a day's first print or after its last are never filled.
+pub fn fill_missing_buckets(chart: &mut PriceChart1H) {
+    let step = chrono::Duration::minutes(chart.bar_minutes as i64);
+    let mut filled: Vec<HourBar> = Vec::with_capacity(chart.bars.len());
+    for bar in chart.bars.drain(..) {
+        if let Some(prev) = filled.last().filter(|p| p.ts_local.date_naive() == bar.ts_local.date_naive()) {
+            let (close, adj_c) = (prev.c, prev.adj_c);
+            let mut next = prev.ts_local + step;
+            while next < bar.ts_local {
+                filled.push(HourBar {
+                    ts_local: next,
+                    o: close,
+                    h: close,
+                    l: close,
+                    c: close,
+                    v: 0,
+                    vwap: close,
+                    notional: 0.0,
+                    sample_count: 0,
+                    adj_c,
+                    synthetic: true,
+                    is_partial: false,
+                    ret_simple: None,
+                    ret_log: None,
+                    sma: None,
+                    ema: None,
+                });
+                next += step;
+            }
+        }
+        filled.push(bar);
+    }
+    chart.bars = filled;
+}
+
+/// Real (non-synthetic) bars one day of a chart holds against the buckets its session has.
+#[derive(Debug, Clone, Serialize)]
+pub struct DayCoverage {
+    pub date: NaiveDate,
+    pub expected: u32,
+    pub actual: u32,
+}
+
+impl DayCoverage {
+    /// Actual bars as a percentage of expected ones.
+    pub fn pct(&self) -> f64 {
+        if self.expected == 0 { 100.0 } else { self.actual as f64 / self.expected as f64 * 100.0 }
+    }
+}
+
+/// Per-day bar counts for every day in `chart`, against the buckets between the session open
+/// and that day's close (half-days included); a trailing partial bucket counts as one.
+pub fn day_coverage(chart: &PriceChart1H) -> Vec<DayCoverage> {
+    let mut actual: BTreeMap<NaiveDate, u32> = BTreeMap::new();
+    for bar in &chart.bars {
+        let count = actual.entry(bar.ts_local.date_naive()).or_default();
+        if !bar.synthetic {
+            *count += 1;
+        }
+    }
+    let bar_minutes = chart.bar_minutes.max(1) as i64;
+    actual
+        .into_iter()
+        .map(|(date, actual)| {
+            let session_minutes = chart.session.minutes_to_close(date, chart.session.open).max(0);
+            let expected = ((session_minutes + bar_minutes - 1) / bar_minutes) as u32;
+            DayCoverage { date, expected, actual }
+        })
+        .collect()
+}
+
+/// Fills `ret_simple`/`ret_log` from each bar's close to the previous bar's close. Returns
+/// reset at every local day, so the first bar of a session has none and the overnight
+/// gap never shows up as a return. Expects bars in chronological order.
+pub fn add_returns(bars: &mut [HourBar]) {
+    let mut prev: Option<(NaiveDate, f64)> = None;
+    for bar in bars.iter_mut() {
+        let day = bar.ts_local.date_naive();
+        (bar.ret_simple, bar.ret_log) = match prev {
+            Some((prev_day, prev_c)) if prev_day == day && prev_c > 0.0 => {
+                (Some(bar.c / prev_c - 1.0), Some((bar.c / prev_c).ln()))
+            },
+            _ => (None, None),
+        };
+        prev = Some((day, bar.c));
+    }
+}
+
+/// Fills `sma` with the mean close of each bar and the `period - 1` bars before it. Unlike
+/// returns this rolls straight across days; the first `period - 1` bars (all of them when
+/// `period` is 0) get None. Expects bars in chronological order.
+pub fn add_sma(bars: &mut [HourBar], period: usize) {
+    let mut sum = 0.0;
+    for i in 0..bars.len() {
+        sum += bars[i].c;
+        if i >= period {
+            sum -= bars[i - period].c;
+        }
+        bars[i].sma = (period > 0 && i + 1 >= period).then(|| sum / period as f64);
+    }
+}
+
+/// Fills `ema` over closes with smoothing 2 / (period + 1), seeded with the simple average of
+/// the first `period` closes. Rolls across days like `add_sma`; bars before the seed get None.
+pub fn add_ema(bars: &mut [HourBar], period: usize) {
+    let alpha = 2.0 / (period as f64 + 1.0);
+    let mut ema: Option<f64> = None;
+    let mut seed = 0.0;
+    for (i, bar) in bars.iter_mut().enumerate() {
+        ema = match ema {
+            Some(prev) => Some(prev + alpha * (bar.c - prev)),
+            None if period == 0 => None,
+            None => {
+                seed += bar.c;
+                (i + 1 == period).then(|| seed / period as f64)
+            }
+        };
+        bar.ema = ema;
+    }
+}
+
+/// Neighbours on each side used for the rolling median in `flag_outliers`.
+const OUTLIER_HALF_WINDOW: usize = 15;
+/// Bars further apart than this start a new run, so halts and session opens aren't compared
+/// against prices from before the break.
+const OUTLIER_MAX_SPACING_SECS: i64 = 30 * 60;
+
+/// Returns indices of bars whose close deviates from the median of its neighbours by more than
+/// `z_threshold` robust standard deviations (1.4826 x MAD, floored at 5bp of the median so flat
+/// tape doesn't flag every tick). Neighbours are the surrounding bars of the same contiguous run,
+/// excluding the bar itself; runs with fewer than 5 bars are never flagged. Expects bars sorted by time.
+pub fn flag_outliers(minutes: &[MinuteBar], z_threshold: f64) -> Vec<usize> {
+    let mut flagged = Vec::new();
+    let mut run_start = 0;
+    for end in 1..=minutes.len() {
+        let breaks = end == minutes.len()
+            || (minutes[end].ts_utc - minutes[end - 1].ts_utc).num_seconds() > OUTLIER_MAX_SPACING_SECS;
+        if !breaks {
+            continue;
+        }
+        let run = &minutes[run_start..end];
+        if run.len() >= 5 {
+            for i in 0..run.len() {
+                let lo = i.saturating_sub(OUTLIER_HALF_WINDOW);
+                let hi = (i + OUTLIER_HALF_WINDOW + 1).min(run.len());
+                let mut window: Vec<f64> = (lo..hi).filter(|&j| j != i).map(|j| run[j].c).collect();
+                let med = median(&mut window);
+                let mut devs: Vec<f64> = window.iter().map(|x| (x - med).abs()).collect();
+                let scale = (1.4826 * median(&mut devs)).max(med.abs() * 0.0005);
+                if scale > 0.0 && (run[i].c - med).abs() / scale > z_threshold {
+                    flagged.push(run_start + i);
+                }
+            }
+        }
+        run_start = end;
+    }
+    flagged
+}
+
+fn median(xs: &mut [f64]) -> f64 {
+    xs.sort_by(|a, b| a.total_cmp(b));
+    let n = xs.len();
+    if n == 0 {
+        0.0
+    } else if n % 2 == 1 {
+        xs[n / 2]
+    } else {
+        (xs[n / 2 - 1] + xs[n / 2]) / 2.0
+    }
+}
+
+/// Bucket width that always spans a whole session, giving one bar per trading day.
+pub const DAILY_BAR_MINUTES: u32 = 24 * 60;
+
+/// One bar per regular NY session: first and last in-session prints for open/close, session
+/// extremes and summed volume. Early closes bound the session like in the intraday path.
+pub fn resample_daily_regular_session(ticker: &str, minutes: &[MinuteBar], window_days: i64) -> PriceChart1H {
+    resample_regular_session(ticker, minutes, window_days, DAILY_BAR_MINUTES)
+}
+
+/// Finds ranges where consecutive regular-session (NY) minute bars are more than `max_gap_secs` apart.
+pub fn find_gaps(minutes: &[MinuteBar], max_gap_secs: i64) -> Vec<Gap> {
+    find_gaps_in_session(minutes, max_gap_secs, &SessionSpec::default())
+}
+
+/// Finds ranges where consecutive in-session minute bars are more than `max_gap_secs` apart.
+/// Only bars on the same local trading day are compared, so the overnight gap and any
+/// weekend/holiday between sessions are never reported.
+pub fn find_gaps_in_session(minutes: &[MinuteBar], max_gap_secs: i64, spec: &SessionSpec) -> Vec<Gap> {
+    let mut in_session: Vec<(NaiveDate, DateTime<Utc>)> = minutes
+        .iter()
+        .filter_map(|b| {
+            let local = b.ts_utc.with_timezone(&spec.tz);
+            let day = local.date_naive();
+            if is_regular_session(&local, spec) && spec.is_trading_day(day) {
+                Some((day, b.ts_utc))
+            } else {
+                None
+            }
+        })
+        .collect();
+    in_session.sort_by_key(|(_, ts)| *ts);
+
+    in_session
+        .windows(2)
+        .filter(|w| w[0].0 == w[1].0 && (w[1].1 - w[0].1).num_seconds() > max_gap_secs)
+        .map(|w| (w[0].1, w[1].1))
+        .collect()
+}
+
+/// Folds the bars of `chart` into one bar per local date: open of the first bar, highest
+/// high, lowest low, close (and adjusted close) of the last bar, summed volume, notional and
+/// sample count, and a volume-weighted vwap. A day is synthetic only if all of its bars are.
+pub fn daily_from_hourly(chart: &PriceChart1H) -> Vec<HourBar> {
+    let mut days: Vec<HourBar> = Vec::new();
+    for b in &chart.bars {
+        match days.last_mut() {
+            Some(day) if day.ts_local.date_naive() == b.ts_local.date_naive() => {
+                day.h = day.h.max(b.h);
+                day.l = day.l.min(b.l);
+                day.c = b.c;
+                day.adj_c = b.adj_c;
+                day.v += b.v;
+                day.vwap += b.vwap * b.v as f64; // running sum(vwap * v), normalized below
+                day.notional += b.notional;
+                day.sample_count += b.sample_count;
+                day.synthetic &= b.synthetic;
+                day.is_partial = b.is_partial;
+            }
+            _ => days.push(HourBar {
+                vwap: b.vwap * b.v as f64,
+                ret_simple: None,
+                ret_log: None,
+                sma: None,
+                ema: None,
+                ..b.clone()
+            }),
+        }
+    }
+    for day in &mut days {
+        day.vwap = if day.v > 0 { day.vwap / day.v as f64 } else { day.c };
+    }
+    days
+}
+
+/// Window-level figures over the bars of a chart; see `summarize`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PriceStats {
+    pub high: Option<f64>,
+    pub low: Option<f64>,
+    pub last_close: Option<f64>,
+    pub total_volume: u64,
+    pub pct_change: Option<f64>, // first open to last close, in percent
+    pub trading_days: usize,
+}
+
+/// Summarizes the kept bars of `chart`. All price fields are None when there are no bars.
+pub fn summarize(chart: &PriceChart1H) -> PriceStats {
+    let (Some(first), Some(last)) = (chart.bars.first(), chart.bars.last()) else {
+        return PriceStats::default();
+    };
+    PriceStats {
+        high: chart.bars.iter().map(|b| b.h).reduce(f64::max),
+        low: chart.bars.iter().map(|b| b.l).reduce(f64::min),
+        last_close: Some(last.c),
+        total_volume: chart.bars.iter().map(|b| b.v).sum(),
+        pct_change: (first.o != 0.0).then(|| (last.c / first.o - 1.0) * 100.0),
+        trading_days: chart.bars.iter().map(|b| b.ts_local.date_naive()).collect::<BTreeSet<_>>().len(),
+    }
+}
+
+/// How a minute series lines up with a session, as reported by `--check`.
+#[derive(Debug, Clone, Default)]
+pub struct SessionCoverage {
+    pub rows: usize,
+    pub in_session: usize, // rows inside the session on a trading day
+    pub trading_days: usize, // local trading days with at least one in-session row
+    pub first: Option<DateTime<Utc>>,
+    pub last: Option<DateTime<Utc>>,
+}
+
+/// Counts how many of `minutes` fall inside `spec`'s session and on how many trading days.
+pub fn session_coverage(minutes: &[MinuteBar], spec: &SessionSpec) -> SessionCoverage {
+    let mut days = BTreeSet::new();
+    let mut in_session = 0;
+    for b in minutes {
+        let local = b.ts_utc.with_timezone(&spec.tz);
+        if is_regular_session(&local, spec) && spec.is_trading_day(local.date_naive()) {
+            in_session += 1;
+            days.insert(local.date_naive());
+        }
+    }
+    SessionCoverage {
+        rows: minutes.len(),
+        in_session,
+        trading_days: days.len(),
+        first: minutes.iter().map(|b| b.ts_utc).min(),
+        last: minutes.iter().map(|b| b.ts_utc).max(),
+    }
+}
+
+/// Which bar survives when several share a timestamp; see `dedup_minutes`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DupPolicy {
+    First,
+    #[default]
+    Last,
+    /// Fail unless every copy of a timestamp is identical.
+    Error,
+}
+
+/// Sorts `bars` by timestamp (stable, so file order decides among equal stamps) and collapses
+/// bars sharing a `ts_utc` into one according to `policy`. Returns how many were dropped.
+/// Identical copies are always collapsed quietly, even under `DupPolicy::Error`.
+pub fn dedup_minutes(bars: &mut Vec<MinuteBar>, policy: DupPolicy) -> Result<usize> {
+    bars.sort_by_key(|b| b.ts_utc);
+    let before = bars.len();
+    let mut out: Vec<MinuteBar> = Vec::with_capacity(before);
+    for b in bars.drain(..) {
+        match out.last_mut() {
+            Some(prev) if prev.ts_utc == b.ts_utc => match policy {
+                DupPolicy::First => {}
+                DupPolicy::Last => *prev = b,
+                DupPolicy::Error if *prev == b => {}
+                DupPolicy::Error => {
+                    return Err(ScrapyError::InvalidInput(format!("conflicting minute bars at {}", b.ts_utc.to_rfc3339())));
+                }
+            },
+            _ => out.push(b),
+        }
+    }
+    *bars = out;
+    Ok(before - bars.len())
+}
+
+/// Split and/or cash dividend taking effect at the open of `ex_date` (session-local date).
+/// `split_ratio` is new shares per old share, e.g. 2.0 for a 2-for-1 split; 1.0 means no split.
+#[derive(Debug, Clone)]
+pub struct CorporateAction {
+    pub ex_date: NaiveDate,
+    pub split_ratio: f64,
+    pub dividend: f64,
+}
+
+/// An OHLC invariant a minute bar breaks; see `validate_ohlc`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OhlcIssue {
+    HighBelowLow,
+    OpenOutsideRange,
+    CloseOutsideRange,
+}
+
+#[derive(Debug, Clone)]
+pub struct OhlcViolation {
+    pub index: usize, // position in the slice passed to `validate_ohlc`
+    pub ts_utc: DateTime<Utc>,
+    pub issue: OhlcIssue,
+}
+
+/// Checks `l <= h` and that `o` and `c` lie in `[l, h]`. A bar breaking several rules shows up
+/// once per rule; the range checks sort high and low first, so an inverted bar is only
+/// reported for its open or close when they lie outside both extremes.
+pub fn validate_ohlc(bars: &[MinuteBar]) -> Vec<OhlcViolation> {
+    let mut out = Vec::new();
+    for (index, b) in bars.iter().enumerate() {
+        let (lo, hi) = (b.l.min(b.h), b.l.max(b.h));
+        let issues = [
+            (b.h < b.l, OhlcIssue::HighBelowLow),
+            (b.o < lo || b.o > hi, OhlcIssue::OpenOutsideRange),
+            (b.c < lo || b.c > hi, OhlcIssue::CloseOutsideRange),
+        ];
+        out.extend(issues.into_iter().filter(|(bad, _)| *bad).map(|(_, issue)| OhlcViolation { index, ts_utc: b.ts_utc, issue }));
+    }
+    out
+}
+
+/// Swaps an inverted high/low, then clamps open and close into `[l, h]`. Returns how many bars
+/// were changed.
+pub fn repair_ohlc(bars: &mut [MinuteBar]) -> usize {
+    let mut repaired = 0;
+    for b in bars.iter_mut() {
+        let before = (b.o, b.h, b.l, b.c);
+        if b.h < b.l {
+            std::mem::swap(&mut b.h, &mut b.l);
+        }
+        // NaN bounds would make clamp panic
+        if b.l <= b.h {
+            b.o = b.o.clamp(b.l, b.h);
+            b.c = b.c.clamp(b.l, b.h);
+        }
+        if (b.o, b.h, b.l, b.c) != before {
+            repaired += 1;
+        }
+    }
+    repaired
+}
+
+/// Back-adjusts OHLC in place so bars before each ex-date are comparable with those after it.
+/// Every action scales earlier prices by `(1 - dividend / prev_close) / split_ratio`, where
+/// `prev_close` is the last unadjusted close before the ex-date, and earlier volume by
+/// `split_ratio`. Factors compound across actions. With no actions the bars are left as-is.
+pub fn apply_corporate_actions(minutes: &mut [MinuteBar], actions: &[CorporateAction], tz: Tz) {
+    if actions.is_empty() {
+        return;
+    }
+    // (ex_date, price factor, volume factor), all taken from the unadjusted series
+    let factors: Vec<(NaiveDate, f64, f64)> = actions
+        .iter()
+        .map(|a| {
+            let prev_close = minutes
+                .iter()
+                .filter(|b| b.ts_utc.with_timezone(&tz).date_naive() < a.ex_date)
+                .max_by_key(|b| b.ts_utc)
+                .map(|b| b.c);
+            let div_factor = match prev_close {
+                Some(c) if a.dividend > 0.0 && a.dividend < c => 1.0 - a.dividend / c,
+                _ => 1.0,
+            };
+            let split = if a.split_ratio > 0.0 { a.split_ratio } else { 1.0 };
+            (a.ex_date, div_factor / split, split)
+        })
+        .collect();
+
+    for b in minutes.iter_mut() {
+        let day = b.ts_utc.with_timezone(&tz).date_naive();
+        let (price_factor, vol_factor) = factors
+            .iter()
+            .filter(|(ex_date, _, _)| day < *ex_date)
+            .fold((1.0, 1.0), |(p, v), (_, fp, fv)| (p * fp, v * fv));
+        b.o *= price_factor;
+        b.h *= price_factor;
+        b.l *= price_factor;
+        b.c *= price_factor;
+        b.v = (b.v as f64 * vol_factor).round() as u64;
+    }
+}
+
+/// True when `dt` falls inside the session on its local date (open inclusive, close exclusive).
+/// Crypto has no session, so every minute counts.
+pub fn is_regular_session(dt: &DateTime<Tz>, spec: &SessionSpec) -> bool {
+    if spec.asset_class == AssetClass::Crypto { return true; }
+    let t = dt.time();
+    // Pre-market: before open
+    if t < spec.open { return false; }
+    // After-hours: close and later (13:00 on half-days)
+    if t >= spec.close_on(dt.date_naive()) { return false; }
     true
 }
 
-/// Returns the start time of the 1-hour bucket (e.g., 09:30, 10:30).
-fn get_bucket_start(dt: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+/// Returns the start time of the bucket (e.g., 09:30, 10:30 for 60-minute buckets on the NY session).
+pub(crate) fn get_bucket_start(dt: &DateTime<Tz>, bucket_minutes: u32, spec: &SessionSpec) -> Option<DateTime<Tz>> {
     let h = dt.hour();
     let m = dt.minute();
+    let width = bucket_minutes as i32;
+    let open_minutes = (spec.open.hour() * 60 + spec.open.minute()) as i32;
     
-    // Calculate minutes since 09:30
-    let minutes_since_930 = (h as i32 - 9) * 60 + (m as i32 - 30);
-    // Bucket index (0 for 09:30-10:29, 1 for 10:30-11:29, etc.)
-    let bucket_idx = minutes_since_930.div_euclid(60); 
+    // Calculate minutes since the session open
+    let minutes_since_open = (h as i32 * 60 + m as i32) - open_minutes;
+    // Bucket index (0 for 09:30-10:29, 1 for 10:30-11:29, etc. with 60-minute buckets)
+    let bucket_idx = minutes_since_open.div_euclid(width); 
     
     // Reconstruct start time
-    let start_minutes_from_midnight = 9 * 60 + 30 + bucket_idx * 60;
+    let start_minutes_from_midnight = open_minutes + bucket_idx * width;
     
     let start_h = (start_minutes_from_midnight / 60) as u32;
     let start_m = (start_minutes_from_midnight % 60) as u32;
     
-    let naive = NaiveDateTime::new(dt.date_naive(), chrono::NaiveTime::from_hms_opt(start_h, start_m, 0)?);
-    naive.and_local_timezone(New_York).single()
+    // Reuse the bar's own UTC offset: re-localizing the naive start through `spec.tz` has no
+    // answer inside a spring-forward gap and two in the repeated fall-back hour
+    let naive = NaiveDateTime::new(dt.date_naive(), NaiveTime::from_hms_opt(start_h, start_m, 0)?);
+    let start = dt.offset().fix().from_local_datetime(&naive).single()?;
+    Some(start.with_timezone(&spec.tz))
+}
+
+/// Formats a bucket width for the packet header (e.g. 60 -> "1h", 15 -> "15m").
+pub fn bar_size_label(bucket_minutes: u32) -> String {
+    if bucket_minutes >= DAILY_BAR_MINUTES {
+        "1d".to_string()
+    } else if bucket_minutes.is_multiple_of(60) {
+        format!("{}h", bucket_minutes / 60)
+    } else {
+        format!("{}m", bucket_minutes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `count` flat one-share minute bars starting at `start` (RFC3339).
+    fn minutes(start: &str, count: i64) -> Vec<MinuteBar> {
+        let start: DateTime<Utc> = start.parse().unwrap();
+        (0..count)
+            .map(|i| MinuteBar { ts_utc: start + chrono::Duration::minutes(i), o: 1.0, h: 1.0, l: 1.0, c: 1.0, v: 1, adj_c: None })
+            .collect()
+    }
+
+    #[test]
+    fn half_day_stops_at_the_1230_bucket() {
+        // 2023-11-24, the day after Thanksgiving: 09:00-16:00 EST of prints
+        let chart = resample_1h_regular_session("T", &minutes("2023-11-24T14:00:00Z", 420), 1);
+        let starts: Vec<String> = chart.bars.iter().map(|b| b.ts_local.format("%H:%M").to_string()).collect();
+        assert_eq!(starts, ["09:30", "10:30", "11:30", "12:30"]);
+        assert_eq!(chart.bars.last().unwrap().sample_count, 30);
+    }
+
+    #[test]
+    fn window_skips_a_holiday_with_stray_prints() {
+        // Presidents' Day 2024-02-19 is a holiday, yet the feed has a full day of prints for it
+        let days = ["2024-02-15", "2024-02-16", "2024-02-19", "2024-02-20", "2024-02-21"];
+        let bars: Vec<MinuteBar> = days.iter().flat_map(|d| minutes(&format!("{}T14:30:00Z", d), 390)).collect();
+        let holiday = NaiveDate::from_ymd_opt(2024, 2, 19).unwrap();
+        let spec = SessionSpec { calendar: TradingCalendar::new([holiday]), ..SessionSpec::default() };
+
+        let chart = resample_session("T", &bars, 3, 60, &spec);
+        let kept: BTreeSet<NaiveDate> = chart.bars.iter().map(|b| b.ts_local.date_naive()).collect();
+        let expected: BTreeSet<NaiveDate> = ["2024-02-16", "2024-02-20", "2024-02-21"].iter().map(|d| d.parse().unwrap()).collect();
+        assert_eq!(kept, expected);
+        assert_eq!(chart.report.days_kept, 3);
+        assert_eq!(chart.bars.len(), 3 * 7);
+    }
+
+    #[test]
+    fn full_first_bucket_has_sixty_samples() {
+        let chart = resample_1h_regular_session("T", &minutes("2024-02-12T14:30:00Z", 390), 1);
+        assert_eq!(chart.bars[0].ts_local.format("%H:%M").to_string(), "09:30");
+        assert_eq!(chart.bars[0].sample_count, 60);
+        // 15:30-16:00 is the half-hour tail of the session
+        assert_eq!(chart.bars.last().unwrap().sample_count, 30);
+    }
+
+    #[test]
+    fn dedup_keeps_the_first_copy_and_counts_the_rest() {
+        let mut bars = minutes("2024-02-12T14:30:00Z", 3);
+        let mut dup = bars[1].clone();
+        dup.c = 2.0;
+        dup.v = 5;
+        bars.push(dup);
+
+        let dropped = dedup_minutes(&mut bars, DupPolicy::First).unwrap();
+        assert_eq!(dropped, 1);
+        assert_eq!(bars.len(), 3);
+        assert_eq!((bars[1].c, bars[1].v), (1.0, 1));
+        assert!(bars.windows(2).all(|w| w[0].ts_utc < w[1].ts_utc));
+    }
+
+    #[test]
+    fn dedup_error_policy_rejects_conflicting_copies() {
+        let mut bars = minutes("2024-02-12T14:30:00Z", 2);
+        bars.push(bars[0].clone());
+        assert_eq!(dedup_minutes(&mut bars.clone(), DupPolicy::Error).unwrap(), 1);
+        bars[2].c = 2.0;
+        assert!(dedup_minutes(&mut bars, DupPolicy::Error).is_err());
+    }
+
+    /// A round-the-clock session on New York time, so the 02:00 DST change falls inside it.
+    fn ny_all_day() -> SessionSpec {
+        SessionSpec { tz: New_York, ..SessionSpec::crypto() }
+    }
+
+    fn local_starts(chart: &PriceChart1H) -> Vec<String> {
+        chart.bars.iter().map(|b| b.ts_local.format("%H:%M%z").to_string()).collect()
+    }
+
+    #[test]
+    fn spring_forward_day_keeps_every_minute() {
+        // 2024-03-10 in New York runs 05:00Z-04:00Z next day: 23 hours, 02:00-02:59 never happens
+        let bars = minutes("2024-03-10T05:00:00Z", 23 * 60);
+        let chart = resample_session("T", &bars, 1, 60, &ny_all_day());
+        assert_eq!(chart.bars.len(), 23);
+        assert_eq!(chart.bars.iter().map(|b| b.sample_count).sum::<u32>(), 23 * 60);
+        let starts = local_starts(&chart);
+        assert_eq!(starts[..3], ["00:00-0500", "01:00-0500", "03:00-0400"]);
+        assert_eq!(starts.last().unwrap(), "23:00-0400");
+    }
+
+    #[test]
+    fn fall_back_day_keeps_both_one_oclock_hours() {
+        // 2024-11-03 in New York runs 04:00Z-05:00Z next day: 25 hours, 01:00-01:59 happens twice
+        let bars = minutes("2024-11-03T04:00:00Z", 25 * 60);
+        let chart = resample_session("T", &bars, 1, 60, &ny_all_day());
+        assert_eq!(chart.bars.len(), 25);
+        assert_eq!(chart.bars.iter().map(|b| b.sample_count).sum::<u32>(), 25 * 60);
+        let starts = local_starts(&chart);
+        assert_eq!(starts[..4], ["00:00-0400", "01:00-0400", "01:00-0500", "02:00-0500"]);
+        assert_eq!(starts.last().unwrap(), "23:00-0500");
+    }
+
+    fn ny_at(h: u32, m: u32) -> DateTime<Tz> {
+        New_York.with_ymd_and_hms(2024, 2, 12, h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn regular_session_boundaries() {
+        let spec = SessionSpec::default();
+        assert!(!is_regular_session(&ny_at(9, 29), &spec));
+        assert!(is_regular_session(&ny_at(9, 30), &spec));
+        assert!(is_regular_session(&ny_at(15, 59), &spec));
+        assert!(!is_regular_session(&ny_at(16, 0), &spec));
+    }
+
+    #[test]
+    fn hourly_bucket_boundaries() {
+        let spec = SessionSpec::default();
+        let bucket = |h, m| get_bucket_start(&ny_at(h, m), 60, &spec);
+        assert_eq!(bucket(9, 30), Some(ny_at(9, 30)));
+        assert_eq!(bucket(10, 29), Some(ny_at(9, 30)));
+        assert_eq!(bucket(10, 30), Some(ny_at(10, 30)));
+        assert_eq!(bucket(15, 59), Some(ny_at(15, 30)));
+    }
+
+    #[test]
+    fn partial_flag_follows_the_input_interval() {
+        // A full day of 5-minute bars: the 15:30 bucket's 6 prints are complete
+        let five: Vec<MinuteBar> = minutes("2024-02-12T14:30:00Z", 390).into_iter().step_by(5).collect();
+        let chart = resample_1h_regular_session("T", &five, 1);
+        let last = chart.bars.last().unwrap();
+        assert_eq!(last.sample_count, 6);
+        assert!(!last.is_partial);
+
+        // One-minute bars that stop at 15:44 leave the 15:30 bucket short
+        let chart = resample_1h_regular_session("T", &minutes("2024-02-12T14:30:00Z", 375), 1);
+        assert!(chart.bars.last().unwrap().is_partial);
+        assert!(!chart.bars[0].is_partial);
+    }
 }
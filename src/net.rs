@@ -0,0 +1,190 @@
+use reqwest::blocking::{Client, Response};
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, ACCEPT_LANGUAGE};
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_RPS: f64 = 2.0;
+
+/// Token bucket refilled at `rps` tokens per second, holding at most `max(rps, 1)` tokens.
+/// A non-positive `rps` disables limiting.
+pub struct RateLimiter {
+    rps: f64,
+    burst: f64,
+    state: Mutex<(f64, Instant)>, // (tokens available, last refill)
+}
+
+impl RateLimiter {
+    pub fn new(rps: f64) -> Self {
+        let burst = rps.max(1.0);
+        Self { rps, burst, state: Mutex::new((burst, Instant::now())) }
+    }
+
+    /// Blocks until a token is available, then takes it.
+    pub fn acquire(&self) {
+        if self.rps <= 0.0 {
+            return;
+        }
+        loop {
+            let wait = {
+                // A panicked holder can't leave the counters inconsistent, so keep going
+                let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+                let (tokens, last) = &mut *state;
+                let now = Instant::now();
+                *tokens = (*tokens + now.duration_since(*last).as_secs_f64() * self.rps).min(self.burst);
+                *last = now;
+                if *tokens >= 1.0 {
+                    *tokens -= 1.0;
+                    return;
+                }
+                Duration::from_secs_f64((1.0 - *tokens) / self.rps)
+            };
+            thread::sleep(wait);
+        }
+    }
+}
+
+static GLOBAL: OnceLock<RateLimiter> = OnceLock::new();
+
+/// Sets the process-wide limit. Only the first call (or `DEFAULT_RPS` if `acquire` ran first) sticks.
+pub fn set_global_rps(rps: f64) {
+    let _ = GLOBAL.set(RateLimiter::new(rps));
+}
+
+/// Waits on the process-wide limiter; call before every outbound blocking request.
+pub fn acquire() {
+    GLOBAL.get_or_init(|| RateLimiter::new(DEFAULT_RPS)).acquire();
+}
+
+/// Desktop browser user agents, rotated per client so consecutive runs don't look identical.
+const USER_AGENTS: &[&str] = &[
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/121.0.0.0 Safari/537.36",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/121.0.0.0 Safari/537.36",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:122.0) Gecko/20100101 Firefox/122.0",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 14_2) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.2 Safari/605.1.15",
+];
+
+/// What browsers send for page navigations; news article hosts are pickier than JSON APIs.
+pub const BROWSER_ACCEPT: &str = "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,*/*;q=0.8";
+
+static USER_AGENT_OVERRIDE: OnceLock<String> = OnceLock::new();
+static NEXT_USER_AGENT: AtomicUsize = AtomicUsize::new(0);
+
+/// Pins every client to `ua` instead of rotating through the built-in pool.
+pub fn set_user_agent(ua: String) {
+    let _ = USER_AGENT_OVERRIDE.set(ua);
+}
+
+/// The `--user-agent` override, if one was set.
+pub fn user_agent_override() -> Option<&'static str> {
+    USER_AGENT_OVERRIDE.get().map(String::as_str)
+}
+
+/// The `--user-agent` override if set, otherwise the next entry of the pool.
+pub fn user_agent() -> String {
+    match USER_AGENT_OVERRIDE.get() {
+        Some(ua) => ua.clone(),
+        None => USER_AGENTS[NEXT_USER_AGENT.fetch_add(1, Ordering::Relaxed) % USER_AGENTS.len()].to_string(),
+    }
+}
+
+static PROXY: OnceLock<reqwest::Proxy> = OnceLock::new();
+static ROOT_CERT: OnceLock<reqwest::Certificate> = OnceLock::new();
+
+/// Routes every client through `url` (http or https) instead of the `HTTPS_PROXY` /
+/// `HTTP_PROXY` environment; hosts in `NO_PROXY` still go direct. Without this call reqwest
+/// reads those variables itself.
+pub fn set_proxy(url: &str) -> reqwest::Result<()> {
+    let proxy = reqwest::Proxy::all(url)?.no_proxy(reqwest::NoProxy::from_env());
+    let _ = PROXY.set(proxy);
+    Ok(())
+}
+
+/// Trusts the PEM (or DER) certificate at `path` in addition to the system roots, e.g. a
+/// corporate TLS-inspecting proxy's CA.
+pub fn set_ca_cert(path: &str) -> crate::error::Result<()> {
+    let bytes = std::fs::read(path)?;
+    let cert = reqwest::Certificate::from_pem(&bytes)
+        .or_else(|_| reqwest::Certificate::from_der(&bytes))
+        .map_err(|e| crate::error::ScrapyError::InvalidInput(format!("not a PEM or DER certificate: {}", e)))?;
+    let _ = ROOT_CERT.set(cert);
+    Ok(())
+}
+
+/// Reads at most `limit` bytes of `resp`'s body. A gzip or deflate response has already been
+/// decoded by reqwest, so the limit applies to the decoded bytes.
+pub fn read_body(resp: Response, limit: u64) -> io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    resp.take(limit).read_to_end(&mut body)?;
+    Ok(body)
+}
+
+/// Async client with the same user agent, proxy and CA settings as `build_client`.
+pub fn build_async_client(cookie_store: bool) -> reqwest::Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .user_agent(user_agent())
+        .gzip(true)
+        .deflate(true)
+        .cookie_store(cookie_store);
+    if let Some(proxy) = PROXY.get() {
+        builder = builder.proxy(proxy.clone());
+    }
+    if let Some(cert) = ROOT_CERT.get() {
+        builder = builder.add_root_certificate(cert.clone());
+    }
+    builder.build()
+}
+
+/// Per-client knobs for `build_client`; the user agent and language headers are shared.
+#[derive(Debug, Clone)]
+pub struct ClientOpts {
+    pub timeout: Option<Duration>,
+    pub cookie_store: bool,
+    pub max_redirects: usize,
+    pub accept: &'static str,
+}
+
+impl Default for ClientOpts {
+    fn default() -> Self {
+        Self {
+            timeout: Some(Duration::from_secs(30)),
+            cookie_store: false,
+            max_redirects: 10,
+            accept: "*/*",
+        }
+    }
+}
+
+impl ClientOpts {
+    pub fn with_timeout(secs: u64) -> Self {
+        Self { timeout: Some(Duration::from_secs(secs)), ..Self::default() }
+    }
+}
+
+/// Builds the blocking client used for every outbound request. Every client asks for gzip or
+/// deflate bodies and reqwest decodes them transparently.
+pub fn build_client(opts: ClientOpts) -> reqwest::Result<Client> {
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT, HeaderValue::from_static(opts.accept));
+    headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_static("en-US,en;q=0.9"));
+
+    let mut builder = Client::builder()
+        .user_agent(user_agent())
+        .default_headers(headers)
+        .redirect(reqwest::redirect::Policy::limited(opts.max_redirects))
+        .gzip(true)
+        .deflate(true)
+        .cookie_store(opts.cookie_store);
+    if let Some(timeout) = opts.timeout {
+        builder = builder.timeout(timeout);
+    }
+    if let Some(proxy) = PROXY.get() {
+        builder = builder.proxy(proxy.clone());
+    }
+    if let Some(cert) = ROOT_CERT.get() {
+        builder = builder.add_root_certificate(cert.clone());
+    }
+    builder.build()
+}
@@ -0,0 +1,1067 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use crate::collectors::{FinanceSnapshotCollector, InsiderCollector, NewsCollector, SenateCollector};
+use crate::collectors::{FinanceSnapshot, InsiderEvent, InstitutionalEvent, NewsItem, SenateItem};
+use crate::collectors::{parse_selector, FallbackPolicy, NewsLocale, NewsSource, RssNewsCollector, SenateStockWatcherCollector, YahooInsiderCollector, YahooSnapshotCollector, DEFAULT_MIN_PARAGRAPH_LEN, DEFAULT_MIN_SNIPPET_LEN, MAX_NEWS_ITEMS};
+use crate::csv_source::{for_each_csv_bar, load_actions, load_csv_bars, load_csv_bars_presorted, write_minute_csv, ColumnMap};
+use crate::error::ScrapyError;
+use crate::market::{add_ema, add_returns, add_sma, apply_corporate_actions, bar_size_label, daily_from_hourly, day_coverage, dedup_minutes, fill_missing_buckets, find_gaps_in_session, flag_outliers, repair_ohlc, resample_session, resample_session_range, summarize, validate_ohlc, DayCoverage, DupPolicy, Gap, HourBar, MinuteBar, OhlcIssue, OhlcViolation, PriceChart1H, PriceStats, SessionSpec, StreamingResampler};
+use crate::provider::{PriceProvider, ProviderMeta};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PacketFormat {
+    Text,
+    Json,
+    /// One JSON record per line: a `bar` per price bar, then one per collected section
+    Ndjson,
+}
+
+/// Layout version of the default text packet, emitted as `TICKER_PACKET_V1`.
+pub const PACKET_VERSION: u32 = 1;
+/// Decimals printed for prices unless `PacketConfig::price_precision` says otherwise.
+pub const DEFAULT_PRICE_PRECISION: usize = 6;
+/// Opening marker of every packet, followed by the version number.
+pub const PACKET_MARKER: &str = "TICKER_PACKET_V";
+
+pub const SECTION_PRICE_BARS: &str = "PRICE_BARS_1H_CSV";
+pub const SECTION_PRICE_BARS_DAILY: &str = "PRICE_BARS_1D_CSV";
+pub const SECTION_PRICE_STATS: &str = "PRICE_STATS";
+pub const SECTION_DATA_GAPS: &str = "DATA_GAPS";
+pub const SECTION_COVERAGE: &str = "COVERAGE";
+pub const SECTION_CHECKSUM: &str = "CHECKSUM";
+pub const SECTION_INSIDER: &str = "INSIDER_AND_INSTITUTIONAL_ACTIVITY";
+pub const SECTION_SENATE: &str = "SENATE_ACTIVITY";
+pub const SECTION_FINANCE: &str = "FINANCE_SNAPSHOT";
+pub const SECTION_NOTES: &str = "NOTES";
+/// Cross-ticker block written ahead of the packets by `--top`; see `render_ranking`.
+pub const SECTION_RANKING: &str = "RANKING";
+
+/// Name of the news section, which carries the item limit and the news window in days,
+/// e.g. `NEWS_TOP10_7D`.
+pub fn news_section(limit: usize, window_days: i64) -> String {
+    format!("NEWS_TOP{}_{}D", limit, window_days)
+}
+
+/// Order of the bars in every output format.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BarOrder {
+    /// Oldest first, the original layout.
+    #[default]
+    Asc,
+    /// Newest first. Returns and moving averages are still computed oldest to newest.
+    Desc,
+}
+
+/// How section markers are spelled in the text packet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DelimiterStyle {
+    /// `<<<NAME>>>` / `<<<END_NAME>>>`, the original layout.
+    #[default]
+    Angle,
+    /// `[[NAME]]` / `[[END_NAME]]`, which survives JSON encoders that escape `<` and `>`.
+    JsonSafe,
+}
+
+impl DelimiterStyle {
+    /// `PACKET_VERSION` for the default style; any other style is version 2 so consumers can branch.
+    pub fn version(self) -> u32 {
+        match self {
+            DelimiterStyle::Angle => PACKET_VERSION,
+            DelimiterStyle::JsonSafe => 2,
+        }
+    }
+
+    pub fn open(self, name: &str) -> String {
+        match self {
+            DelimiterStyle::Angle => format!("<<<{}>>>", name),
+            DelimiterStyle::JsonSafe => format!("[[{}]]", name),
+        }
+    }
+
+    pub fn close(self, name: &str) -> String {
+        self.open(&format!("END_{}", name))
+    }
+}
+
+/// Body of a section whose collector failed: a single comment line between the usual delimiters.
+fn error_line(e: &str) -> String {
+    format!("# error: {}", e.lines().next().unwrap_or_default())
+}
+
+/// Outcome of an optional packet section.
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", content = "data", rename_all = "snake_case")]
+pub enum Section<T> {
+    Skipped,
+    Ok(T),
+    Error(String),
+}
+
+impl<T> Section<T> {
+    /// "ok", "error" or "skipped", as in the JSON `status` tag.
+    pub fn status(&self) -> &'static str {
+        match self {
+            Section::Skipped => "skipped",
+            Section::Ok(_) => "ok",
+            Section::Error(_) => "error",
+        }
+    }
+
+    /// Runs a collector for an optional section. A failure is logged and kept as
+    /// `Section::Error` so the rest of the packet is still produced.
+    pub(crate) fn collect<E: std::fmt::Display>(name: &str, ticker: &str, enabled: bool, f: impl FnOnce() -> std::result::Result<T, E>) -> Self {
+        if !enabled {
+            return Section::Skipped;
+        }
+        match f() {
+            Ok(v) => Section::Ok(v),
+            Err(e) => {
+                log::warn!("{}: {} unavailable: {}", ticker, name, e);
+                Section::Error(e.to_string())
+            }
+        }
+    }
+
+    /// `collect`, unless `deadline` has already passed: then the section is an error without
+    /// running and its name goes into `late`.
+    fn collect_by<E: std::fmt::Display>(name: &'static str, ticker: &str, enabled: bool, deadline: Option<Instant>, late: &mut Vec<&'static str>, f: impl FnOnce() -> std::result::Result<T, E>) -> Self {
+        if enabled && deadline.is_some_and(|d| Instant::now() >= d) {
+            log::warn!("{}: deadline reached, {} not collected", ticker, name);
+            late.push(name);
+            return Section::Error("deadline reached before this section ran".to_string());
+        }
+        Section::collect(name, ticker, enabled, f)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct InsiderActivity {
+    pub trades: Vec<InsiderEvent>,
+    pub holders: Vec<InstitutionalEvent>,
+}
+
+/// `COVERAGE` section: the days whose bar count fell short of `threshold_pct`.
+#[derive(Debug, Serialize)]
+pub struct CoverageReport {
+    pub threshold_pct: u32,
+    pub days_checked: usize,
+    pub short_days: Vec<DayCoverage>,
+}
+
+/// Everything gathered for one symbol, independent of the output format.
+#[derive(Debug, Serialize)]
+pub struct TickerPacket {
+    pub ticker: String,
+    /// Quote currency and listing exchange from the provider; None for CSV sources
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exchange: Option<String>,
+    pub tz: String,
+    pub session: String,
+    pub window_days: i64,
+    pub bar_size: String,
+    pub bars: Vec<HourBar>,
+    #[serde(skip)]
+    pub returns: bool,
+    #[serde(skip)]
+    pub sma_period: Option<usize>,
+    #[serde(skip)]
+    pub ema_period: Option<usize>,
+    /// Decimals for prices in the text format
+    #[serde(skip)]
+    pub price_precision: usize,
+    /// Order `bars` and `daily` are stored (and rendered) in
+    #[serde(skip)]
+    pub bar_order: BarOrder,
+    /// Add the `partial` column to the text format
+    #[serde(skip)]
+    pub mark_partial: bool,
+    /// Add the `notional` column to the text format
+    #[serde(skip)]
+    pub notional: bool,
+    /// Key text-format bars by `HH:MM` under a `DATE:` line per day
+    #[serde(skip)]
+    pub compact_bars: bool,
+    pub stats: PriceStats,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily: Option<Vec<HourBar>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gaps: Option<Vec<Gap>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<CoverageReport>,
+    pub news_limit: usize,
+    pub news: Section<Vec<NewsItem>>,
+    pub insider: Section<InsiderActivity>,
+    pub senate: Section<Vec<SenateItem>>,
+    pub finance: Section<Option<FinanceSnapshot>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+}
+
+/// Where the minute bars for a packet come from.
+pub enum PriceSource {
+    /// A local CSV; see `csv_source::load_csv_bars`.
+    Csv { path: String, assume_tz: Tz, columns: ColumnMap },
+    Provider { provider: Box<dyn PriceProvider>, interval: String, range: String },
+}
+
+/// Everything `build_packet` needs for one symbol. `PacketConfig::new` fills in the CLI
+/// defaults; adjust the public fields from there.
+pub struct PacketConfig {
+    pub ticker: String,
+    pub source: PriceSource,
+    pub window_days: i64,
+    pub bar_minutes: u32,
+    pub session: SessionSpec,
+    /// Corporate actions CSV to back-adjust the minute bars with, if any
+    pub actions_file: Option<String>,
+    /// How to collapse minute bars sharing a timestamp
+    pub dup_policy: DupPolicy,
+    /// Keep the trading days in this inclusive local-date range instead of the last `window_days`
+    pub date_range: Option<(NaiveDate, NaiveDate)>,
+    /// Read a CSV source row by row instead of loading it whole; see `stream_chart`
+    pub stream: bool,
+    /// Don't sort a CSV source; fail on the first out-of-order record instead
+    pub assume_sorted: bool,
+    /// Emit a packet with a warning note instead of failing when a CSV source has no data rows
+    pub allow_empty: bool,
+    /// Report minute bars breaking `l <= o, c <= h` in NOTES
+    pub validate_ohlc: bool,
+    /// Also fix them: swap an inverted high/low and clamp open/close into the range
+    pub repair_ohlc: bool,
+    /// Write the loaded minute bars (after duplicate collapsing) to this CSV for later runs
+    pub save_csv: Option<String>,
+    /// Drop minute bars beyond this many robust standard deviations, if set
+    pub outlier_z: Option<f64>,
+    pub fill_gaps: bool,
+    pub returns: bool,
+    /// Add an `sma_N` column: rolling mean of the last N closes, across days
+    pub sma: Option<usize>,
+    /// Add an `ema_N` column: exponential moving average of closes, across days
+    pub ema: Option<usize>,
+    /// Decimals for prices in the text packet (0-8)
+    pub price_precision: usize,
+    /// Emit bars oldest first (default) or newest first
+    pub bar_order: BarOrder,
+    /// Flag each day's last bar when it has fewer prints than its input interval fits (a `partial` column)
+    pub mark_partial: bool,
+    /// Add a `notional` column: sum of close * volume over each bar's minutes
+    pub notional: bool,
+    /// Write the intraday bars as `HH:MM` rows under a `DATE:` line per day (text format)
+    pub compact_bars: bool,
+    /// Also fold the bars into one per local day (`PRICE_BARS_1D_CSV`)
+    pub include_daily: bool,
+    /// Report intraday holes longer than this many seconds, if set
+    pub max_gap_secs: Option<i64>,
+    /// List days whose real bars fall below this percentage of the session's buckets, if set
+    pub min_coverage_pct: Option<u32>,
+    pub news: bool,
+    pub news_source: NewsSource,
+    pub news_count: usize,
+    pub news_concurrency: usize,
+    /// Honour publishers' robots.txt when scraping article bodies
+    pub respect_robots: bool,
+    pub news_min_paragraph_len: usize,
+    pub news_min_snippet_len: usize,
+    /// What a news item shows when its article body couldn't be scraped
+    pub news_fallback: FallbackPolicy,
+    /// Truncate each snippet near this many characters, at a sentence boundary
+    pub news_snippet_max_chars: Option<usize>,
+    /// Keep at most this many sentences of each snippet
+    pub news_snippet_max_sentences: Option<usize>,
+    /// Keep feeds here and revalidate them with conditional GETs, if set
+    pub news_feed_cache: Option<PathBuf>,
+    /// Language and region of the Google News / Yahoo Finance feeds
+    pub news_locale: NewsLocale,
+    /// Don't fetch article pages; snippets are the feeds' own descriptions
+    pub news_headlines_only: bool,
+    /// CSS selectors tried, in order, for article paragraphs before the readability heuristic
+    pub news_content_selectors: Vec<String>,
+    pub insider: bool,
+    pub senate: bool,
+    pub finance: bool,
+    /// Start no further network requests for the optional sections after this instant
+    pub deadline: Option<Instant>,
+}
+
+impl PacketConfig {
+    pub fn new(ticker: impl Into<String>, source: PriceSource) -> Self {
+        Self {
+            ticker: ticker.into(),
+            source,
+            window_days: 7,
+            bar_minutes: 60,
+            session: SessionSpec::default(),
+            actions_file: None,
+            dup_policy: DupPolicy::Last,
+            date_range: None,
+            stream: false,
+            assume_sorted: false,
+            allow_empty: false,
+            validate_ohlc: false,
+            repair_ohlc: false,
+            save_csv: None,
+            outlier_z: None,
+            fill_gaps: false,
+            returns: false,
+            sma: None,
+            ema: None,
+            price_precision: DEFAULT_PRICE_PRECISION,
+            bar_order: BarOrder::Asc,
+            mark_partial: false,
+            notional: false,
+            compact_bars: false,
+            include_daily: false,
+            max_gap_secs: None,
+            min_coverage_pct: None,
+            news: true,
+            news_source: NewsSource::Google,
+            news_count: 10,
+            news_concurrency: 4,
+            respect_robots: true,
+            news_min_paragraph_len: DEFAULT_MIN_PARAGRAPH_LEN,
+            news_min_snippet_len: DEFAULT_MIN_SNIPPET_LEN,
+            news_fallback: FallbackPolicy::RssDescription,
+            news_snippet_max_chars: None,
+            news_snippet_max_sentences: None,
+            news_feed_cache: None,
+            news_locale: NewsLocale::default(),
+            news_headlines_only: false,
+            news_content_selectors: Vec::new(),
+            insider: true,
+            senate: true,
+            finance: true,
+            deadline: None,
+        }
+    }
+}
+
+/// Fetches, resamples and collects everything for one symbol.
+pub fn build_packet(config: PacketConfig) -> Result<TickerPacket> {
+    let ticker = config.ticker.as_str();
+    if let Some((from, to)) = config.date_range {
+        if from > to {
+            bail!("date range start {} is after its end {}", from, to);
+        }
+    }
+    let mut notes = Vec::new();
+    let (mut chart, gaps, meta) = if config.stream {
+        let (chart, gaps) = stream_chart(&config, &mut notes)?;
+        (chart, gaps, None)
+    } else {
+        load_chart(&config, &mut notes)?
+    };
+    resample_notes(&chart, &mut notes);
+
+    // Before forward-filling, though synthetic bars wouldn't count anyway
+    let coverage = config.min_coverage_pct.map(|threshold_pct| {
+        let days = day_coverage(&chart);
+        let days_checked = days.len();
+        let short_days = days.into_iter().filter(|d| d.pct() < threshold_pct as f64).collect();
+        CoverageReport { threshold_pct, days_checked, short_days }
+    });
+    if config.fill_gaps {
+        fill_missing_buckets(&mut chart);
+    }
+    if config.returns {
+        add_returns(&mut chart.bars);
+    }
+    if let Some(period) = config.sma {
+        add_sma(&mut chart.bars, period);
+    }
+    if let Some(period) = config.ema {
+        add_ema(&mut chart.bars, period);
+    }
+    let stats = summarize(&chart);
+    let mut daily = config.include_daily.then(|| daily_from_hourly(&chart));
+    // Only now that everything derived from the series is computed in time order
+    if config.bar_order == BarOrder::Desc {
+        chart.bars.reverse();
+        if let Some(daily) = &mut daily {
+            daily.reverse();
+        }
+    }
+
+    // 3. Collect Extra Data (Live!)
+    let window_days = config.window_days;
+    let news_limit = config.news_count.min(MAX_NEWS_ITEMS);
+    // The price section is done; past the deadline the remaining sections aren't started
+    let deadline = config.deadline;
+    let mut late = Vec::new();
+    let news = Section::collect_by("news", ticker, config.news, deadline, &mut late, || {
+        let content_selectors = config.news_content_selectors.iter().map(|css| parse_selector(css)).collect::<std::result::Result<_, _>>()?;
+        RssNewsCollector {
+            source: config.news_source,
+            max_items: news_limit,
+            concurrency: config.news_concurrency,
+            respect_robots: config.respect_robots,
+            min_paragraph_len: config.news_min_paragraph_len,
+            min_snippet_len: config.news_min_snippet_len,
+            fallback: config.news_fallback,
+            snippet_max_chars: config.news_snippet_max_chars,
+            snippet_max_sentences: config.news_snippet_max_sentences,
+            feed_cache: config.news_feed_cache.clone(),
+            locale: config.news_locale.clone(),
+            headlines_only: config.news_headlines_only,
+            deadline,
+            content_selectors,
+            ..RssNewsCollector::default()
+        }
+        .collect_news(ticker, window_days)
+    });
+    let news_cut_short = matches!(news, Section::Ok(_)) && !config.news_headlines_only && deadline.is_some_and(|d| Instant::now() >= d);
+    // Pass the window_days for strict filtering!
+    let insider = Section::collect_by("insider", ticker, config.insider, deadline, &mut late, || {
+        let (trades, holders) = YahooInsiderCollector.collect_activity(ticker, window_days)?;
+        Ok::<_, ScrapyError>(InsiderActivity { trades, holders })
+    });
+    let senate = Section::collect_by("senate", ticker, config.senate, deadline, &mut late, || SenateStockWatcherCollector.collect_senate(ticker, window_days));
+    let finance = Section::collect_by("finance", ticker, config.finance, deadline, &mut late, || YahooSnapshotCollector.collect_snapshot(ticker, meta.as_ref()));
+    if news_cut_short {
+        notes.push("WARNING: deadline reached while collecting news; some articles may show their feed summary instead of the page".to_string());
+    }
+    if !late.is_empty() {
+        notes.push(format!("WARNING: deadline reached; {} not collected", late.join(", ")));
+    }
+    let (currency, exchange) = meta.map(|m| (m.currency, m.exchange)).unwrap_or_default();
+
+    Ok(TickerPacket {
+        tz: chart.session.tz.name().to_string(),
+        session: chart.session.label(),
+        window_days: chart.window_days,
+        bar_size: bar_size_label(chart.bar_minutes),
+        ticker: chart.ticker,
+        currency,
+        exchange,
+        bars: chart.bars,
+        returns: config.returns,
+        sma_period: config.sma,
+        ema_period: config.ema,
+        price_precision: config.price_precision,
+        bar_order: config.bar_order,
+        mark_partial: config.mark_partial,
+        notional: config.notional,
+        compact_bars: config.compact_bars,
+        stats,
+        daily,
+        gaps,
+        coverage,
+        news_limit,
+        news,
+        insider,
+        senate,
+        finance,
+        notes,
+    })
+}
+
+/// Loads every minute bar into memory, cleans them up and resamples.
+fn load_chart(config: &PacketConfig, notes: &mut Vec<String>) -> Result<(PriceChart1H, Option<Vec<Gap>>, Option<ProviderMeta>)> {
+    let ticker = config.ticker.as_str();
+    let session = &config.session;
+    let (mut rows, meta) = match &config.source {
+        PriceSource::Csv { path, assume_tz, columns } => {
+            let load = if config.assume_sorted { load_csv_bars_presorted } else { load_csv_bars };
+            let rows = load(path, *assume_tz, columns).with_context(|| format!("Failed to load price data from {}", path))?;
+            if rows.is_empty() {
+                no_rows(path, config, notes)?;
+            }
+            (rows, None)
+        },
+        PriceSource::Provider { provider, interval, range } => provider.fetch(ticker, interval, range)
+            .with_context(|| format!("Failed to fetch price data for {}", ticker))?,
+    };
+
+    let dropped = dedup_minutes(&mut rows, config.dup_policy).with_context(|| format!("Duplicate timestamps in price data for {}", ticker))?;
+    notes.extend(dup_note(dropped, config.dup_policy));
+
+    if config.validate_ohlc || config.repair_ohlc {
+        let violations = validate_ohlc(&rows);
+        let repaired = if config.repair_ohlc { repair_ohlc(&mut rows) } else { 0 };
+        notes.push(ohlc_note(&violations, config.repair_ohlc.then_some(repaired)));
+    }
+
+    if let Some(path) = &config.save_csv {
+        write_minute_csv(path, &rows).with_context(|| format!("Failed to save price data to {}", path))?;
+        log::info!("Saved {} minute bar(s) to {}", rows.len(), path);
+    }
+
+    if let Some(path) = &config.actions_file {
+        let actions = load_actions(path).with_context(|| format!("Failed to load corporate actions from {}", path))?;
+        apply_corporate_actions(&mut rows, &actions, session.tz);
+    }
+
+    if let Some(z) = config.outlier_z {
+        let flagged = flag_outliers(&rows, z);
+        if !flagged.is_empty() {
+            let mut idx = 0;
+            rows.retain(|_| {
+                let keep = flagged.binary_search(&idx).is_err();
+                idx += 1;
+                keep
+            });
+        }
+        notes.push(format!("dropped {} outlier minute bar(s) (z > {})", flagged.len(), z));
+    }
+
+    let chart = match config.date_range {
+        Some((from, to)) => {
+            let span = rows.first().zip(rows.last()).map(|(a, b)| (a.ts_utc, b.ts_utc));
+            notes.extend(range_note(from, to, span, session));
+            resample_session_range(ticker, &rows, from, to, config.bar_minutes, session)
+        }
+        None => resample_session(ticker, &rows, config.window_days, config.bar_minutes, session),
+    };
+    let gaps = config.max_gap_secs.map(|secs| find_gaps_in_session(&rows, secs, session));
+    Ok((chart, gaps, meta))
+}
+
+/// What the resampler kept, and a warning when bars came in but none were in session.
+fn resample_notes(chart: &PriceChart1H, notes: &mut Vec<String>) {
+    let r = chart.report;
+    if r.input_bars == 0 {
+        return;
+    }
+    if r.in_session_bars == 0 {
+        notes.push(format!(
+            "WARNING: none of the {} minute bar(s) fall inside the {} {} session on a trading day; the data may be out of session or in the wrong timezone (see --assume-tz)",
+            r.input_bars,
+            chart.session.hours_label(),
+            chart.session.tz.name(),
+        ));
+        return;
+    }
+    notes.push(format!(
+        "{} of {} minute bar(s) in session across {} trading day(s); {} day(s) kept",
+        r.in_session_bars, r.input_bars, r.days_seen, r.days_kept
+    ));
+}
+
+/// A CSV without data rows is an error, unless `allow_empty` turns it into a packet note.
+fn no_rows(path: &str, config: &PacketConfig, notes: &mut Vec<String>) -> Result<()> {
+    if !config.allow_empty {
+        bail!("no price rows parsed from {}", path);
+    }
+    notes.push(format!("WARNING: no price rows parsed from {}; the price sections are empty", path));
+    Ok(())
+}
+
+/// `--stream` path: reads a time-sorted CSV row by row, collapsing duplicates on the fly, and
+/// resamples day by day so memory stays bounded by the window. Matches `load_chart` on
+/// sorted input; options that need the whole series up front are rejected.
+fn stream_chart(config: &PacketConfig, notes: &mut Vec<String>) -> Result<(PriceChart1H, Option<Vec<Gap>>)> {
+    let ticker = config.ticker.as_str();
+    let PriceSource::Csv { path, assume_tz, columns } = &config.source else {
+        bail!("streaming only applies to CSV sources");
+    };
+    if config.actions_file.is_some() || config.outlier_z.is_some() {
+        bail!("streaming can't be combined with corporate-action adjustment or outlier removal");
+    }
+    if config.save_csv.is_some() {
+        bail!("streaming can't save the minute bars to CSV");
+    }
+
+    let mut resampler = StreamingResampler::new(ticker, config.window_days, config.bar_minutes, &config.session, config.max_gap_secs);
+    if let Some((from, to)) = config.date_range {
+        resampler = resampler.with_range(from, to);
+    }
+    let mut span: Option<(DateTime<Utc>, DateTime<Utc>)> = None;
+    let mut pending: Option<MinuteBar> = None;
+    let mut dropped = 0;
+    let mut ohlc = OhlcTally { enabled: config.validate_ohlc || config.repair_ohlc, ..OhlcTally::default() };
+    for_each_csv_bar(path, *assume_tz, columns, |record_no, bar| {
+        span = Some((span.map_or(bar.ts_utc, |(first, _)| first), bar.ts_utc));
+        match &mut pending {
+            Some(prev) if bar.ts_utc < prev.ts_utc => {
+                return Err(ScrapyError::CsvParse(format!(
+                    "record {}: timestamp {} is earlier than the row before it; streaming needs a time-sorted CSV",
+                    record_no, bar.ts_utc.to_rfc3339())));
+            }
+            Some(prev) if bar.ts_utc == prev.ts_utc => {
+                dropped += 1;
+                match config.dup_policy {
+                    DupPolicy::First => {}
+                    DupPolicy::Last => *prev = bar,
+                    DupPolicy::Error if *prev == bar => {}
+                    DupPolicy::Error => {
+                        return Err(ScrapyError::InvalidInput(format!("conflicting minute bars at {}", bar.ts_utc.to_rfc3339())));
+                    }
+                }
+            }
+            _ => {
+                if let Some(mut prev) = pending.replace(bar) {
+                    ohlc.check(&mut prev, config.repair_ohlc);
+                    resampler.push(prev);
+                }
+            }
+        }
+        Ok(())
+    }).with_context(|| format!("Failed to stream price data from {}", path))?;
+    if let Some(mut last) = pending {
+        ohlc.check(&mut last, config.repair_ohlc);
+        resampler.push(last);
+    }
+    if span.is_none() {
+        no_rows(path, config, notes)?;
+    }
+    notes.extend(dup_note(dropped, config.dup_policy));
+    if ohlc.enabled {
+        notes.push(ohlc_note(&ohlc.violations, config.repair_ohlc.then_some(ohlc.repaired)));
+    }
+    if let Some((from, to)) = config.date_range {
+        notes.extend(range_note(from, to, span, &config.session));
+    }
+
+    let (chart, gaps) = resampler.finish();
+    Ok((chart, config.max_gap_secs.map(|_| gaps)))
+}
+
+/// Note for a `date_range` reaching past the data, whose first/last timestamps are `span`.
+fn range_note(from: NaiveDate, to: NaiveDate, span: Option<(DateTime<Utc>, DateTime<Utc>)>, session: &SessionSpec) -> Option<String> {
+    let Some((first, last)) = span else {
+        return Some(format!("date range {}..{} requested but the source has no data", from, to));
+    };
+    let (first, last) = (first.with_timezone(&session.tz).date_naive(), last.with_timezone(&session.tz).date_naive());
+    (from < first || to > last).then(|| {
+        format!("date range {}..{} extends beyond the data ({}..{}); clamped to what is available", from, to, first, last)
+    })
+}
+
+fn dup_note(dropped: usize, policy: DupPolicy) -> Option<String> {
+    let kept = match policy {
+        DupPolicy::First => "kept first",
+        DupPolicy::Last => "kept last",
+        DupPolicy::Error => "identical copies",
+    };
+    (dropped > 0).then(|| format!("collapsed {} duplicate minute bar(s) ({})", dropped, kept))
+}
+
+/// Running `validate_ohlc`/`repair_ohlc` over the bars `stream_chart` hands to the resampler.
+#[derive(Default)]
+struct OhlcTally {
+    enabled: bool,
+    seen: usize,
+    violations: Vec<OhlcViolation>,
+    repaired: usize,
+}
+
+impl OhlcTally {
+    fn check(&mut self, bar: &mut MinuteBar, repair: bool) {
+        if !self.enabled {
+            return;
+        }
+        self.violations.extend(validate_ohlc(std::slice::from_ref(bar)).into_iter().map(|v| OhlcViolation { index: self.seen, ..v }));
+        self.seen += 1;
+        if repair {
+            self.repaired += repair_ohlc(std::slice::from_mut(bar));
+        }
+    }
+}
+
+/// NOTES line for `--validate-ohlc`/`--repair-ohlc`: violation counts per rule, the first
+/// offending bar and, when repairing, how many bars were changed.
+fn ohlc_note(violations: &[OhlcViolation], repaired: Option<usize>) -> String {
+    let Some(first) = violations.first() else {
+        return "OHLC check: no violations".to_string();
+    };
+    for v in violations {
+        log::debug!("OHLC violation at {}: {:?}", v.ts_utc.to_rfc3339(), v.issue);
+    }
+    let count = |issue| violations.iter().filter(|v| v.issue == issue).count();
+    let mut note = format!(
+        "OHLC check: {} violation(s) (high<low {}, open outside range {}, close outside range {}), first at {}",
+        violations.len(), count(OhlcIssue::HighBelowLow), count(OhlcIssue::OpenOutsideRange), count(OhlcIssue::CloseOutsideRange), first.ts_utc.to_rfc3339(),
+    );
+    if let Some(n) = repaired {
+        note.push_str(&format!("; repaired {} bar(s)", n));
+    }
+    note
+}
+
+/// Writes the `# ts_local,...` (or compact `# time,...`) header and one CSV row per bar. The
+/// `adj_c` and `synthetic` columns appear when some bar has data for them; `partial`,
+/// `notional` and, with `indicators` (intraday bars only), the returns and moving averages
+/// follow `p`'s flags. Prices use `p.price_precision` decimals, returns always six.
+fn push_bars_csv(out: &mut String, bars: &[HourBar], p: &TickerPacket, indicators: bool) {
+    let (returns, sma, ema) = if indicators { (p.returns, p.sma_period, p.ema_period) } else { (false, None, None) };
+    let compact = indicators && p.compact_bars;
+    let (precision, partial, notional) = (p.price_precision, p.mark_partial, p.notional);
+    // Adjusted close only shows up for daily+ intervals, so the column is emitted on demand
+    let has_adj = bars.iter().any(|b| b.adj_c.is_some());
+    let has_synthetic = bars.iter().any(|b| b.synthetic);
+    out.push_str(if compact { "# time,o,h,l,c,v,vwap,n" } else { "# ts_local,o,h,l,c,v,vwap,n" });
+    if has_adj {
+        out.push_str(",adj_c");
+    }
+    if has_synthetic {
+        out.push_str(",synthetic");
+    }
+    if partial {
+        out.push_str(",partial");
+    }
+    if notional {
+        out.push_str(",notional");
+    }
+    if returns {
+        out.push_str(",ret_simple,ret_log");
+    }
+    if let Some(period) = sma {
+        out.push_str(&format!(",sma_{}", period));
+    }
+    if let Some(period) = ema {
+        out.push_str(&format!(",ema_{}", period));
+    }
+    out.push('\n');
+    let mut day = None;
+    for b in bars {
+        // Compact rows carry only the local HH:MM, under a DATE line whenever the day changes
+        let ts = if compact {
+            let date = b.ts_local.date_naive();
+            if day != Some(date) {
+                day = Some(date);
+                out.push_str(&format!("DATE: {}\n", date));
+            }
+            b.ts_local.format("%H:%M").to_string()
+        } else {
+            b.ts_local.to_rfc3339()
+        };
+        out.push_str(&format!("{},{:.p$},{:.p$},{:.p$},{:.p$},{},{:.p$},{}", ts, b.o, b.h, b.l, b.c, b.v, b.vwap, b.sample_count, p = precision));
+        if has_adj {
+            out.push_str(&b.adj_c.map(|a| format!(",{:.p$}", a, p = precision)).unwrap_or_else(|| ",".to_string()));
+        }
+        if has_synthetic {
+            out.push_str(if b.synthetic { ",1" } else { ",0" });
+        }
+        if partial {
+            out.push_str(if b.is_partial { ",1" } else { ",0" });
+        }
+        if notional {
+            out.push_str(&format!(",{:.2}", b.notional));
+        }
+        if returns {
+            // First bar of each session is left blank rather than NaN
+            for r in [b.ret_simple, b.ret_log] {
+                out.push_str(&r.map(|r| format!(",{:.6}", r)).unwrap_or_else(|| ",".to_string()));
+            }
+        }
+        // Leading bars without enough history are left blank
+        for (period, value) in [(sma, b.sma), (ema, b.ema)] {
+            if period.is_some() {
+                out.push_str(&value.map(|x| format!(",{:.p$}", x, p = precision)).unwrap_or_else(|| ",".to_string()));
+            }
+        }
+        out.push('\n');
+    }
+}
+
+/// Renders a packet in the delimited `<<<SECTION>>>` text format.
+fn render_text(p: &TickerPacket, style: DelimiterStyle) -> String {
+    let news_block = match &p.news {
+        Section::Ok(items) => {
+            if items.is_empty() {
+                "No recent news found.".to_string()
+            } else {
+                 items.iter().take(p.news_limit).map(|item| {
+                     format!("{} | {} | {} | sentiment {:+.2}\nurl: {}\n{}\n-------------------",
+                        item.datetime, item.source, item.headline, item.sentiment, item.url, item.content_snippet)
+                 }).collect::<Vec<_>>().join("\n")
+            }
+        }
+        Section::Error(e) => error_line(e),
+        Section::Skipped => String::new(),
+    };
+
+    let insider_block = match &p.insider {
+        Section::Ok(activity) => {
+            let mut s = String::new();
+            if activity.trades.is_empty() {
+                s.push_str(&format!("--- RECENT INSIDER TRANSACTIONS (Last {} Days) ---\n", p.window_days));
+                s.push_str("No transactions found in this period.\n");
+            } else {
+                s.push_str(&format!("--- RECENT INSIDER TRANSACTIONS (Last {} Days) ---\n", p.window_days));
+                s.push_str("# Date | Entity | Relation | Type | Value\n");
+                for t in &activity.trades {
+                    s.push_str(&format!("{} | {} | {} | {} | {}\n", t.date, t.entity_name, t.relation, t.transaction_type, t.value_approx));
+                }
+            }
+            
+            s.push_str("\n--- TOP INSTITUTIONAL & FUND HOLDERS ---\n");
+            s.push_str("# Holder | Type | % Held\n");
+            for h in &activity.holders {
+                 s.push_str(&format!("{} | {} | {}\n", h.holder_name, h.holder_type.label(), h.pct_held));
+            }
+            s
+        },
+        Section::Error(e) => error_line(e),
+        Section::Skipped => String::new(),
+    };
+
+    let senate_block = match &p.senate {
+        Section::Ok(items) => {
+            let mut s = format!("--- SENATE TRADING DISCLOSURES (Last {} Days) ---\n", p.window_days);
+            if items.is_empty() {
+                s.push_str("No disclosures found in this period.\n");
+            } else {
+                s.push_str("# Date | Chamber | Member | Type | Amount | Min | Max\n");
+                for item in items {
+                    // Blank bounds when the amount didn't parse; Max is also blank for the open-ended top bucket
+                    let bound = |b: Option<f64>| b.map(|v| format!("{:.0}", v)).unwrap_or_default();
+                    s.push_str(&format!("{} | {} | {} | {} | {} | {} | {}\n", item.date, item.chamber, item.member_name, item.activity_type, item.notes, bound(item.amount_min), bound(item.amount_max)));
+                }
+            }
+            s
+        }
+        Section::Error(e) => error_line(e),
+        Section::Skipped => String::new(),
+    };
+
+    let finance_block = match &p.finance {
+        Section::Ok(Some(s)) => {
+            let mut block = format!(
+                "source: {}\nasof_utc: {}\nprice_last: {}\n",
+                s.source, s.asof_utc, s.price_last
+            );
+            if let Some(mc) = s.market_cap_approx {
+                block.push_str(&format!("market_cap_approx: {}\n", mc));
+            }
+            if let Some(pe) = s.pe_ratio_approx {
+                block.push_str(&format!("pe_ratio_approx: {}\n", pe));
+            }
+            block.push_str(&format!("notes: \"{}\"\n", s.notes));
+            block
+        },
+        // Unlike the sections above, no newline is added after this block
+        Section::Ok(None) => "No snapshot available.\n".to_string(),
+        Section::Error(e) => format!("{}\n", error_line(e)),
+        Section::Skipped => String::new(),
+    };
+
+    // 4. Build Packet String
+    let mut packet = String::new();
+    packet.push_str(&format!("{}\n", style.open(&format!("{}{}", PACKET_MARKER, style.version()))));
+    packet.push_str(&format!("TICKER: {}\n", p.ticker));
+    if let Some(currency) = &p.currency {
+        packet.push_str(&format!("CURRENCY: {}\n", currency));
+    }
+    if let Some(exchange) = &p.exchange {
+        packet.push_str(&format!("EXCHANGE: {}\n", exchange));
+    }
+    packet.push_str(&format!("TZ: {}\n", p.tz));
+    packet.push_str(&format!("SESSION: {}\n", p.session));
+    packet.push_str(&format!("WINDOW_DAYS: {}\n", p.window_days));
+    packet.push_str(&format!("BAR_SIZE: {}\n", p.bar_size));
+    packet.push_str(&format!("BARS_COUNT: {}\n", p.bars.len()));
+    packet.push('\n');
+
+    packet.push_str(&format!("{}\n", style.open(SECTION_PRICE_BARS)));
+    push_bars_csv(&mut packet, &p.bars, p, true);
+    packet.push_str(&format!("{}\n", style.close(SECTION_PRICE_BARS)));
+    packet.push('\n');
+
+    if let Some(daily) = &p.daily {
+        packet.push_str(&format!("{}\n", style.open(SECTION_PRICE_BARS_DAILY)));
+        push_bars_csv(&mut packet, daily, p, false);
+        packet.push_str(&format!("{}\n", style.close(SECTION_PRICE_BARS_DAILY)));
+        packet.push('\n');
+    }
+
+    let num = |x: Option<f64>| x.map(|x| format!("{:.p$}", x, p = p.price_precision)).unwrap_or_else(|| "N/A".to_string());
+    packet.push_str(&format!("{}\n", style.open(SECTION_PRICE_STATS)));
+    packet.push_str(&format!("high: {}\n", num(p.stats.high)));
+    packet.push_str(&format!("low: {}\n", num(p.stats.low)));
+    packet.push_str(&format!("last_close: {}\n", num(p.stats.last_close)));
+    packet.push_str(&format!("total_volume: {}\n", p.stats.total_volume));
+    packet.push_str(&format!("pct_change: {}\n", p.stats.pct_change.map(|x| format!("{:+.2}%", x)).unwrap_or_else(|| "N/A".to_string())));
+    packet.push_str(&format!("trading_days: {}\n", p.stats.trading_days));
+    packet.push_str(&format!("{}\n", style.close(SECTION_PRICE_STATS)));
+    packet.push('\n');
+
+    if let Some(gaps) = &p.gaps {
+        packet.push_str(&format!("{}\n", style.open(SECTION_DATA_GAPS)));
+        packet.push_str("# from_utc,to_utc,gap_secs\n");
+        for (from, to) in gaps {
+            packet.push_str(&format!("{},{},{}\n", from.to_rfc3339(), to.to_rfc3339(), (*to - *from).num_seconds()));
+        }
+        packet.push_str(&format!("{}\n", style.close(SECTION_DATA_GAPS)));
+        packet.push('\n');
+    }
+
+    if let Some(coverage) = &p.coverage {
+        packet.push_str(&format!("{}\n", style.open(SECTION_COVERAGE)));
+        packet.push_str(&format!("# {} of {} day(s) below {}% of expected bars\n", coverage.short_days.len(), coverage.days_checked, coverage.threshold_pct));
+        packet.push_str("# date,expected,actual,pct\n");
+        for day in &coverage.short_days {
+            packet.push_str(&format!("{},{},{},{:.1}%\n", day.date, day.expected, day.actual, day.pct()));
+        }
+        packet.push_str(&format!("{}\n", style.close(SECTION_COVERAGE)));
+        packet.push('\n');
+    }
+
+    let news_name = news_section(p.news_limit, p.window_days);
+    packet.push_str(&format!("{}\n", style.open(&news_name)));
+    if !news_block.is_empty() {
+        packet.push_str(&news_block);
+        packet.push('\n');
+    }
+    packet.push_str(&format!("{}\n", style.close(&news_name)));
+    packet.push('\n');
+
+    packet.push_str(&format!("{}\n", style.open(SECTION_INSIDER)));
+     if !insider_block.is_empty() {
+        packet.push_str(&insider_block);
+        packet.push('\n');
+    }
+    packet.push_str(&format!("{}\n", style.close(SECTION_INSIDER)));
+    packet.push('\n');
+
+    packet.push_str(&format!("{}\n", style.open(SECTION_SENATE)));
+    if !senate_block.is_empty() {
+        packet.push_str(&senate_block);
+        packet.push('\n');
+    }
+    packet.push_str(&format!("{}\n", style.close(SECTION_SENATE)));
+    packet.push('\n');
+
+    packet.push_str(&format!("{}\n", style.open(SECTION_FINANCE)));
+    if !finance_block.is_empty() {
+        packet.push_str(&finance_block);
+    }
+    packet.push_str(&format!("{}\n", style.close(SECTION_FINANCE)));
+    packet.push('\n');
+
+    if !p.notes.is_empty() {
+        packet.push_str(&format!("{}\n", style.open(SECTION_NOTES)));
+        for note in &p.notes {
+            packet.push_str(&format!("- {}\n", note));
+        }
+        packet.push_str(&format!("{}\n", style.close(SECTION_NOTES)));
+        packet.push('\n');
+    }
+
+    packet
+}
+
+/// CRC-32 (IEEE, as in zlib and gzip) of `bytes`.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// Ends a text packet with a `CHECKSUM` section: the CRC-32 and line count of everything after
+/// the `TICKER_PACKET` marker line, so a consumer can tell a truncated or altered packet.
+pub fn append_checksum(packet: &mut String, style: DelimiterStyle) {
+    let body = packet.split_once('\n').map(|(_, body)| body).unwrap_or_default();
+    let (crc, lines) = (crc32(body.as_bytes()), body.lines().count());
+    packet.push_str(&format!("{}\n", style.open(SECTION_CHECKSUM)));
+    packet.push_str(&format!("crc32: {:08x}\n", crc));
+    packet.push_str(&format!("lines: {}\n", lines));
+    packet.push_str(&format!("{}\n", style.close(SECTION_CHECKSUM)));
+}
+
+/// Renders one packet as the delimited `<<<SECTION>>>` text or a pretty-printed JSON object.
+pub fn render_packet(packet: &TickerPacket, format: PacketFormat) -> String {
+    render_packet_with_style(packet, format, DelimiterStyle::default())
+}
+
+/// Like `render_packet`, with the section markers spelled in `style` (text format only).
+pub fn render_packet_with_style(packet: &TickerPacket, format: PacketFormat, style: DelimiterStyle) -> String {
+    match format {
+        PacketFormat::Text => render_text(packet, style),
+        PacketFormat::Json => {
+            // Plain data with string keys; serde_json cannot fail on it
+            let mut json = serde_json::to_string_pretty(packet).expect("packet serializes to JSON");
+            json.push('\n');
+            json
+        }
+        PacketFormat::Ndjson => render_ndjson(packet),
+    }
+}
+
+/// A single NDJSON line: the record type and symbol ahead of the record's own fields.
+#[derive(Serialize)]
+struct NdjsonRecord<'a, T: Serialize> {
+    #[serde(rename = "type")]
+    kind: &'a str,
+    ticker: &'a str,
+    #[serde(flatten)]
+    data: T,
+}
+
+fn push_ndjson<T: Serialize>(out: &mut String, kind: &str, ticker: &str, data: T) {
+    let record = NdjsonRecord { kind, ticker, data };
+    out.push_str(&serde_json::to_string(&record).expect("record serializes to JSON"));
+    out.push('\n');
+}
+
+/// Bars as `{"type":"bar","ticker":...}` lines, followed by a `news`, `insider`, `senate` and
+/// `finance` line (`status` and `data` as in the JSON format) for each section not skipped.
+fn render_ndjson(p: &TickerPacket) -> String {
+    let mut out = String::new();
+    for bar in &p.bars {
+        push_ndjson(&mut out, "bar", &p.ticker, bar);
+    }
+    if !matches!(p.news, Section::Skipped) {
+        push_ndjson(&mut out, "news", &p.ticker, &p.news);
+    }
+    if !matches!(p.insider, Section::Skipped) {
+        push_ndjson(&mut out, "insider", &p.ticker, &p.insider);
+    }
+    if !matches!(p.senate, Section::Skipped) {
+        push_ndjson(&mut out, "senate", &p.ticker, &p.senate);
+    }
+    if !matches!(p.finance, Section::Skipped) {
+        push_ndjson(&mut out, "finance", &p.ticker, &p.finance);
+    }
+    out
+}
+
+/// One symbol's row in the `RANKING` block, from its window `PriceStats`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RankEntry {
+    pub ticker: String,
+    pub pct_change: Option<f64>,
+    pub total_volume: u64,
+    /// Whether a full packet follows for this symbol
+    pub selected: bool,
+}
+
+/// Renders the ranking as a delimited CSV block, entries in the order given.
+pub fn render_ranking(entries: &[RankEntry], rank_by: &str, style: DelimiterStyle) -> String {
+    let mut out = format!("{}\nRANK_BY: {}\nrank,ticker,pct_change,total_volume,packet\n", style.open(SECTION_RANKING), rank_by);
+    for (i, e) in entries.iter().enumerate() {
+        let change = e.pct_change.map(|x| format!("{:+.2}", x)).unwrap_or_else(|| "N/A".to_string());
+        out.push_str(&format!("{},{},{},{},{}\n", i + 1, e.ticker, change, e.total_volume, if e.selected { "yes" } else { "no" }));
+    }
+    out.push_str(&format!("{}\n\n", style.close(SECTION_RANKING)));
+    out
+}
+
+/// The ranking as NDJSON, one `{"type":"rank","rank_by":...}` line per entry.
+pub fn render_ranking_ndjson(entries: &[RankEntry], rank_by: &str) -> String {
+    #[derive(Serialize)]
+    struct RankRecord<'a> {
+        #[serde(rename = "type")]
+        kind: &'a str,
+        rank_by: &'a str,
+        #[serde(flatten)]
+        entry: &'a RankEntry,
+    }
+    let mut out = String::new();
+    for entry in entries {
+        out.push_str(&serde_json::to_string(&RankRecord { kind: "rank", rank_by, entry }).expect("record serializes to JSON"));
+        out.push('\n');
+    }
+    out
+}
@@ -0,0 +1,148 @@
+use crate::error::{Result, ScrapyError};
+use crate::fetcher::{self, ChartCache, YahooMeta};
+use crate::market::MinuteBar;
+use crate::net::{self, ClientOpts};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Europe::Warsaw;
+
+/// Provider-neutral metadata returned alongside price bars.
+#[derive(Debug, Clone)]
+pub struct ProviderMeta {
+    pub source: String, // e.g. "YahooChartMeta", "Stooq"
+    pub symbol: String,
+    pub currency: Option<String>,
+    pub regular_market_price: Option<f64>,
+    pub previous_close: Option<f64>,
+    /// When `regular_market_price` was traded, if the provider says
+    pub market_time: Option<DateTime<Utc>>,
+    /// Listing exchange, if the provider names it
+    pub exchange: Option<String>,
+}
+
+impl From<YahooMeta> for ProviderMeta {
+    fn from(m: YahooMeta) -> Self {
+        Self {
+            source: "YahooChartMeta".to_string(),
+            symbol: m.symbol,
+            currency: m.currency,
+            regular_market_price: m.regular_market_price,
+            previous_close: m.chart_previous_close,
+            market_time: m.regular_market_time.and_then(|t| DateTime::from_timestamp(t, 0)),
+            exchange: m.full_exchange_name.or(m.exchange_name),
+        }
+    }
+}
+
+/// A source of minute-level (or coarser intraday) bars. `interval`/`range` use Yahoo-style
+/// strings (`1m`, `5d`, ...); providers reject combinations they can't serve.
+pub trait PriceProvider {
+    fn fetch(&self, ticker: &str, interval: &str, range: &str) -> Result<(Vec<MinuteBar>, Option<ProviderMeta>)>;
+}
+
+pub struct YahooProvider {
+    pub max_retries: u32,
+    pub cache: Option<ChartCache>,
+    /// Chart hosts in failover order; empty means `fetcher::DEFAULT_YAHOO_HOSTS`
+    pub hosts: Vec<String>,
+}
+
+impl PriceProvider for YahooProvider {
+    fn fetch(&self, ticker: &str, interval: &str, range: &str) -> Result<(Vec<MinuteBar>, Option<ProviderMeta>)> {
+        let hosts: Vec<&str> = if self.hosts.is_empty() {
+            fetcher::DEFAULT_YAHOO_HOSTS.to_vec()
+        } else {
+            self.hosts.iter().map(String::as_str).collect()
+        };
+        let (bars, meta) = fetcher::fetch_bars_cached(ticker, interval, range, self.max_retries, self.cache.as_ref(), &hosts)?;
+        Ok((bars, meta.map(ProviderMeta::from)))
+    }
+}
+
+/// Intraday bars from stooq.com's CSV download. Stooq has no 1m data and stamps bars in
+/// Polish local time; plain US symbols get the `.us` suffix Stooq expects.
+pub struct StooqProvider;
+
+impl StooqProvider {
+    fn stooq_interval(interval: &str) -> Result<&'static str> {
+        match interval {
+            "5m" => Ok("5"),
+            "15m" => Ok("15"),
+            "30m" => Ok("30"),
+            "60m" | "1h" => Ok("60"),
+            _ => Err(ScrapyError::InvalidInput(format!("Stooq has no '{}' bars (use 5m, 15m, 30m or 1h)", interval))),
+        }
+    }
+
+    fn stooq_symbol(ticker: &str) -> String {
+        let t = ticker.to_lowercase();
+        if t.contains('.') { t } else { format!("{}.us", t) }
+    }
+}
+
+impl PriceProvider for StooqProvider {
+    fn fetch(&self, ticker: &str, interval: &str, range: &str) -> Result<(Vec<MinuteBar>, Option<ProviderMeta>)> {
+        let i = Self::stooq_interval(interval)?;
+        let url = format!("https://stooq.com/q/d/l/?s={}&i={}", Self::stooq_symbol(ticker), i);
+        let client = net::build_client(ClientOpts::with_timeout(15))?;
+        net::acquire();
+        let resp = client.get(&url).send()?;
+        if !resp.status().is_success() {
+            return Err(ScrapyError::Http(resp.status()));
+        }
+        let body = resp.text()?;
+
+        // Unknown symbols come back as a bare "No data" line instead of a CSV
+        let mut rdr = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(body.as_bytes());
+        let headers = rdr.headers().map_err(|e| ScrapyError::CsvParse(e.to_string()))?.clone();
+        let col = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+        let (Some(date), Some(time), Some(o), Some(h), Some(l), Some(c)) =
+            (col("Date"), col("Time"), col("Open"), col("High"), col("Low"), col("Close"))
+        else {
+            return Err(ScrapyError::NoData);
+        };
+        let v = col("Volume");
+
+        let cutoff = fetcher::range_days(range).map(|d| Utc::now() - chrono::Duration::days(d));
+        let mut bars = Vec::new();
+        for (n, record) in rdr.records().enumerate() {
+            let record = record.map_err(|e| ScrapyError::CsvParse(format!("record {}: {}", n + 1, e)))?;
+            let get = |idx: usize| record.get(idx).unwrap_or_default();
+            let bad = |name: &str, raw: &str| ScrapyError::CsvParse(format!("record {}: bad {} value '{}'", n + 1, name, raw));
+            let price = |idx: usize, name: &str| get(idx).parse::<f64>().map_err(|_| bad(name, get(idx)));
+
+            let stamp = format!("{} {}", get(date), get(time));
+            let naive = NaiveDateTime::parse_from_str(&stamp, "%Y-%m-%d %H:%M:%S").map_err(|_| bad("Date/Time", &stamp))?;
+            let ts_utc = Warsaw.from_local_datetime(&naive).earliest()
+                .ok_or_else(|| bad("Date/Time", &stamp))?
+                .with_timezone(&Utc);
+            if cutoff.is_some_and(|cut| ts_utc < cut) {
+                continue;
+            }
+            bars.push(MinuteBar {
+                ts_utc,
+                o: price(o, "Open")?,
+                h: price(h, "High")?,
+                l: price(l, "Low")?,
+                c: price(c, "Close")?,
+                // Volume is occasionally fractional or missing for thin symbols
+                v: v.and_then(|idx| get(idx).parse::<f64>().ok()).map(|x| x.max(0.0).round() as u64).unwrap_or(0),
+                adj_c: None,
+            });
+        }
+        if bars.is_empty() {
+            return Err(ScrapyError::NoData);
+        }
+        bars.sort_by_key(|b| b.ts_utc);
+
+        let meta = ProviderMeta {
+            source: "Stooq".to_string(),
+            symbol: ticker.to_uppercase(),
+            currency: None,
+            regular_market_price: bars.last().map(|b| b.c),
+            previous_close: None,
+            market_time: bars.last().map(|b| b.ts_utc),
+            exchange: None,
+        };
+        Ok((bars, Some(meta)))
+    }
+}
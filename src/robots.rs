@@ -0,0 +1,136 @@
+use crate::net::{self, ClientOpts};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// (allow, path pattern)
+type Rule = (bool, String);
+
+/// The Allow/Disallow rules of one robots.txt that apply to us.
+#[derive(Debug, Clone, Default)]
+pub struct RobotsRules {
+    rules: Vec<Rule>,
+}
+
+impl RobotsRules {
+    /// Everything disallowed; used when robots.txt can't be read because the server is failing.
+    pub fn deny_all() -> Self {
+        Self { rules: vec![(false, "/".to_string())] }
+    }
+
+    /// Parses robots.txt and keeps the groups naming `user_agent` (case-insensitive substring
+    /// of the product token), falling back to the `*` groups when none do.
+    pub fn parse(body: &str, user_agent: Option<&str>) -> Self {
+        let ua = user_agent.map(str::to_lowercase);
+        let mut groups: Vec<(Vec<String>, Vec<Rule>)> = Vec::new();
+        let mut in_rules = false;
+        for line in body.lines() {
+            let line = line.split('#').next().unwrap_or_default();
+            let Some((key, value)) = line.split_once(':') else { continue };
+            let value = value.trim();
+            match key.trim().to_lowercase().as_str() {
+                "user-agent" => {
+                    // A user-agent line after rules opens a new group
+                    if in_rules || groups.is_empty() {
+                        groups.push((Vec::new(), Vec::new()));
+                        in_rules = false;
+                    }
+                    if let Some(group) = groups.last_mut() {
+                        group.0.push(value.to_lowercase());
+                    }
+                }
+                key @ ("allow" | "disallow") => {
+                    in_rules = true;
+                    // An empty Disallow allows everything, so it adds no rule
+                    if let (Some(group), false) = (groups.last_mut(), value.is_empty()) {
+                        group.1.push((key == "allow", value.to_string()));
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        let named = |agent: &String| agent != "*" && ua.as_deref().is_some_and(|ua| ua.contains(agent.as_str()));
+        let specific = groups.iter().any(|(agents, _)| agents.iter().any(named));
+        let rules = groups.into_iter()
+            .filter(|(agents, _)| if specific { agents.iter().any(named) } else { agents.iter().any(|a| a == "*") })
+            .flat_map(|(_, rules)| rules)
+            .collect();
+        Self { rules }
+    }
+
+    /// Longest matching pattern wins, Allow on a tie; no match means allowed.
+    /// `path` is the URL path plus query, e.g. `/news/a?id=1`.
+    pub fn allows(&self, path: &str) -> bool {
+        if path == "/robots.txt" {
+            return true;
+        }
+        self.rules.iter()
+            .filter(|(_, pattern)| pattern_matches(pattern, path))
+            .max_by_key(|(allow, pattern)| (pattern.len(), *allow))
+            .is_none_or(|(allow, _)| *allow)
+    }
+}
+
+/// robots.txt path patterns: a prefix match where `*` spans any run of characters and a
+/// trailing `$` anchors the end.
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(p) => (p, true),
+        None => (pattern, false),
+    };
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if parts.len() == 1 {
+        return if anchored { path == first } else { path.starts_with(first) };
+    }
+    let Some(mut rest) = path.strip_prefix(first) else { return false };
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    if anchored { rest.ends_with(last) } else { rest.contains(last) }
+}
+
+/// Rules per `scheme://host:port`, fetched once per run. The per-host cell keeps concurrent
+/// article scrapes on the same site from fetching robots.txt more than once.
+static CACHE: OnceLock<Mutex<HashMap<String, Arc<OnceLock<RobotsRules>>>>> = OnceLock::new();
+
+/// Whether robots.txt on `url`'s host lets us fetch it. A missing robots.txt (4xx) allows
+/// everything; a server error or unreachable host disallows the whole site for this run.
+/// Non-HTTP or unparseable URLs are allowed.
+pub fn allowed(url: &str) -> bool {
+    let Ok(parsed) = reqwest::Url::parse(url) else { return true };
+    let (Some(host), true) = (parsed.host_str(), parsed.scheme().starts_with("http")) else { return true };
+    let origin = match parsed.port() {
+        Some(port) => format!("{}://{}:{}", parsed.scheme(), host, port),
+        None => format!("{}://{}", parsed.scheme(), host),
+    };
+
+    let cell = {
+        let mut cache = CACHE.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+        cache.entry(origin.clone()).or_default().clone()
+    };
+    let rules = cell.get_or_init(|| fetch_rules(&origin));
+
+    let path = match parsed.query() {
+        Some(q) => format!("{}?{}", parsed.path(), q),
+        None => parsed.path().to_string(),
+    };
+    rules.allows(&path)
+}
+
+fn fetch_rules(origin: &str) -> RobotsRules {
+    let Ok(client) = net::build_client(ClientOpts::with_timeout(5)) else { return RobotsRules::deny_all() };
+    net::acquire();
+    log::debug!("GET {}/robots.txt", origin);
+    match client.get(format!("{}/robots.txt", origin)).send() {
+        Ok(resp) if resp.status().is_success() => match resp.text() {
+            Ok(body) => RobotsRules::parse(&body, net::user_agent_override()),
+            Err(_) => RobotsRules::deny_all(),
+        },
+        Ok(resp) if resp.status().is_client_error() => RobotsRules::default(),
+        _ => RobotsRules::deny_all(),
+    }
+}
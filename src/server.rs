@@ -0,0 +1,136 @@
+//! Minimal blocking HTTP front end for `weekchart serve`: `GET /packet?ticker=AAPL&window_days=7&format=json`
+//! answers with the rendered packet. One thread per connection, `Connection: close`, no
+//! keep-alive or request bodies. Packets are cached per (ticker, window, format) for a short TTL
+//! so repeat requests don't hit the upstreams again.
+
+use crate::packet::PacketFormat;
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// What a `/packet` request asks for. `window_days` is None when the query doesn't set it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PacketRequest {
+    pub ticker: String, // uppercased
+    pub window_days: Option<i64>,
+    pub format: PacketFormat,
+}
+
+/// Rendered packets by request, with the time they were built.
+type PacketCache = Mutex<HashMap<PacketRequest, (Instant, String)>>;
+
+/// Accepts connections on `listener` until it fails, building packets with `handler` (an error
+/// becomes a 500 with the message as body). Successful packets are reused for `ttl`.
+pub fn serve<F>(listener: TcpListener, ttl: Duration, handler: F) -> io::Result<()>
+where
+    F: Fn(&PacketRequest) -> Result<String, String> + Sync,
+{
+    let cache: PacketCache = Mutex::new(HashMap::new());
+    thread::scope(|scope| {
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let (cache, handler) = (&cache, &handler);
+            scope.spawn(move || {
+                if let Err(e) = handle(stream, ttl, cache, handler) {
+                    log::warn!("connection failed: {}", e);
+                }
+            });
+        }
+        Ok(())
+    })
+}
+
+fn handle<F>(stream: TcpStream, ttl: Duration, cache: &PacketCache, handler: &F) -> io::Result<()>
+where
+    F: Fn(&PacketRequest) -> Result<String, String>,
+{
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Drain the headers; nothing in them matters here
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+
+    let (status, content_type, body) = match respond(request_line.trim_end(), ttl, cache, handler) {
+        Ok((content_type, body)) => (200, content_type, body),
+        Err((status, message)) => (status, "text/plain; charset=utf-8", format!("{}\n", message)),
+    };
+    log::info!("{} -> {}", request_line.trim_end(), status);
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    let mut out = &stream;
+    write!(out, "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", status, reason, content_type, body.len())?;
+    out.write_all(body.as_bytes())?;
+    out.flush()
+}
+
+/// (content type, body) for a request line, or (status, message).
+fn respond<F>(request_line: &str, ttl: Duration, cache: &PacketCache, handler: &F) -> Result<(&'static str, String), (u16, String)>
+where
+    F: Fn(&PacketRequest) -> Result<String, String>,
+{
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+    if method != "GET" {
+        return Err((405, format!("{} not supported, use GET", method)));
+    }
+    let url = reqwest::Url::parse(&format!("http://localhost{}", target)).map_err(|e| (400, format!("bad request target: {}", e)))?;
+    if url.path() != "/packet" {
+        return Err((404, "not found; try GET /packet?ticker=AAPL".to_string()));
+    }
+    let request = parse_query(&url).map_err(|e| (400, e))?;
+
+    if let Some((built, body)) = cache.lock().unwrap().get(&request) {
+        if built.elapsed() < ttl {
+            return Ok((content_type(request.format), body.clone()));
+        }
+    }
+    let body = handler(&request).map_err(|e| (500, e))?;
+    cache.lock().unwrap().insert(request.clone(), (Instant::now(), body.clone()));
+    Ok((content_type(request.format), body))
+}
+
+/// Reads `ticker` (required), `window_days` and `format` (text, json or ndjson; default text).
+fn parse_query(url: &reqwest::Url) -> Result<PacketRequest, String> {
+    let mut request = PacketRequest { ticker: String::new(), window_days: None, format: PacketFormat::Text };
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "ticker" => request.ticker = value.trim().to_uppercase(),
+            "window_days" => {
+                let days = value.parse::<i64>().ok().filter(|d| *d >= 1).ok_or_else(|| format!("window_days must be a positive integer, got {:?}", value))?;
+                request.window_days = Some(days);
+            }
+            "format" => {
+                request.format = match value.as_ref() {
+                    "text" => PacketFormat::Text,
+                    "json" => PacketFormat::Json,
+                    "ndjson" => PacketFormat::Ndjson,
+                    other => return Err(format!("format must be text, json or ndjson, got {:?}", other)),
+                }
+            }
+            _ => {}
+        }
+    }
+    if request.ticker.is_empty() {
+        return Err("missing ticker".to_string());
+    }
+    Ok(request)
+}
+
+fn content_type(format: PacketFormat) -> &'static str {
+    match format {
+        PacketFormat::Text => "text/plain; charset=utf-8",
+        PacketFormat::Json => "application/json",
+        PacketFormat::Ndjson => "application/x-ndjson",
+    }
+}
@@ -0,0 +1,44 @@
+//! End-to-end packet output over a committed fixture CSV, compared byte for byte with the
+//! golden files in `tests/golden/`. After an intended output change, regenerate them with
+//! `UPDATE_GOLDEN=1 cargo test --test golden` and review the diff.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use weekchart::csv_source::ColumnMap;
+use weekchart::{build_packet, render_packet, PacketConfig, PacketFormat, PriceSource};
+
+fn manifest_path(rel: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(rel)
+}
+
+/// Builds the fixture packet with every network section turned off.
+fn fixture_packet(format: PacketFormat) -> String {
+    let source = PriceSource::Csv {
+        path: manifest_path("tests/fixtures/golden_minutes.csv").to_string_lossy().into_owned(),
+        assume_tz: chrono_tz::UTC,
+        columns: ColumnMap::default(),
+    };
+    let config = PacketConfig { news: false, insider: false, senate: false, finance: false, ..PacketConfig::new("GOLD", source) };
+    render_packet(&build_packet(config).expect("fixture packet builds"), format)
+}
+
+fn check_golden(name: &str, actual: &str) {
+    let path = manifest_path(&format!("tests/golden/{}", name));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {} (run with UPDATE_GOLDEN=1 to create it)", path.display(), e));
+    assert!(expected == actual, "{} differs from the golden file; rerun with UPDATE_GOLDEN=1 if the change is intended\n--- actual ---\n{}", name, actual);
+}
+
+#[test]
+fn text_packet_matches_golden() {
+    check_golden("packet.txt", &fixture_packet(PacketFormat::Text));
+}
+
+#[test]
+fn json_packet_matches_golden() {
+    check_golden("packet.json", &fixture_packet(PacketFormat::Json));
+}